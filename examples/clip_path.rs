@@ -11,7 +11,7 @@ fn main() {
     let clip_path = clip_path.transform(Transform::from_row(1.0, -0.3, 0.0, 1.0, 0.0, 75.0)).unwrap();
 
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(500, 500, &clip_path, FillRule::EvenOdd, true);
+    clip_mask.set_path(500, 500, &clip_path, FillRule::EvenOdd, true, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
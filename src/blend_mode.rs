@@ -1,4 +1,4 @@
-use crate::pipeline;
+use crate::pipeline::{self, RasterPipelineBuilder};
 
 /// A blending mode.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -128,4 +128,42 @@ impl BlendMode {
             BlendMode::Luminosity       => Some(pipeline::Stage::Luminosity),
         }
     }
+
+    pub(crate) fn push_stages(self, p: &mut RasterPipelineBuilder) {
+        if let Some(stage) = self.to_stage() {
+            p.push(stage);
+        }
+    }
+}
+
+
+/// A custom blender.
+///
+/// Unlike `BlendMode`, is parametrized and can express blending functions
+/// that Porter-Duff modes cannot.
+///
+/// Essentially a `SkBlender`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Blender {
+    /// `k1 * s * d + k2 * s + k3 * d + k4`, applied to each premultiplied channel.
+    ///
+    /// The result is clamped to a valid premultiplied color.
+    ///
+    /// The same as SVG `feComposite` with `operator="arithmetic"`.
+    /// Unlike SVG, affects only the painted area, even when `k4` is not zero.
+    ///
+    /// Essentially a `SkBlenders::Arithmetic`.
+    #[allow(missing_docs)]
+    Arithmetic { k1: f32, k2: f32, k3: f32, k4: f32 },
+}
+
+impl Blender {
+    pub(crate) fn push_stages(self, p: &mut RasterPipelineBuilder) {
+        match self {
+            Blender::Arithmetic { k1, k2, k3, k4 } => {
+                p.ctx.arithmetic = pipeline::ArithmeticCtx { k1, k2, k3, k4 };
+                p.push(pipeline::Stage::Arithmetic);
+            }
+        }
+    }
 }
@@ -3,30 +3,283 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
+use alloc::vec;
 use alloc::vec::Vec;
 
-use crate::{Path, LengthU32, FillRule};
+use crate::{Path, PathBuilder, LengthU32, FillRule, IntRect, Point, Rect, Region, Transform};
+use crate::{PixmapRef, PremultipliedColorU8, Stencil, StencilFunc};
 use crate::{ALPHA_U8_OPAQUE, ALPHA_U8_TRANSPARENT};
 
 use crate::alpha_runs::AlphaRun;
 use crate::blitter::Blitter;
-use crate::color::AlphaU8;
+use crate::color::{AlphaU8, premultiply_u8};
+use crate::floating_point::SaturateRound;
 use crate::geom::ScreenIntRect;
 use crate::math::LENGTH_U32_ONE;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
 use core::num::NonZeroU32;
 
+/// A horizontal run of pixels with the same coverage.
+#[derive(Copy, Clone, Debug)]
+pub struct ClipRun {
+    pub x: u32,
+    pub width: LengthU32,
+    pub alpha: AlphaU8,
+}
+
+/// A part of a horizontal span after clipping.
+#[derive(Copy, Clone, Debug)]
+pub struct ClipSegment {
+    pub x: u32,
+    pub width: LengthU32,
+    // Whether the segment is partially covered and must be masked.
+    // Otherwise, it's fully visible.
+    pub masked: bool,
+}
+
+// A run-length encoded mask.
+//
+// Only non-transparent runs are stored, so fully visible and fully clipped
+// areas cost almost nothing.
 #[derive(Clone, Debug)]
 pub struct ClipMaskData {
-    pub data: Vec<u8>,
+    // Non-transparent runs, sorted by y and x.
+    pub runs: Vec<ClipRun>,
+    // Runs of the row `y` are `runs[rows[y - rect.y]..rows[y - rect.y + 1]]`.
+    pub rows: Vec<u32>,
+    // The size of the pixmap the mask was created for.
     pub width: LengthU32,
     pub height: LengthU32,
+    // The region covered by `rows`. Everything outside of it is fully clipped.
+    pub rect: ScreenIntRect,
+    // A rectangular clip. `runs` are unused and everything inside `rect` is visible.
+    pub is_rect: bool,
 }
 
 impl ClipMaskData {
-    pub(crate) fn clip_mask_ctx(&self) -> crate::pipeline::ClipMaskCtx {
-        crate::pipeline::ClipMaskCtx {
-            data: &self.data,
-            stride: self.width,
+    /// Resets the mask to the specified size and storage region, without any runs.
+    fn reset(&mut self, width: LengthU32, height: LengthU32, rect: ScreenIntRect) {
+        self.width = width;
+        self.height = height;
+        self.rect = rect;
+        self.is_rect = false;
+
+        // Reuse the existing allocation.
+        self.runs.clear();
+        self.rows.clear();
+        self.rows.push(0);
+    }
+
+    /// Resets the mask to a fully clipped one.
+    fn reset_clipped(&mut self, width: LengthU32, height: LengthU32) {
+        let rect = ScreenIntRect::from_xywh_safe(0, 0, LENGTH_U32_ONE, LENGTH_U32_ONE);
+        self.reset(width, height, rect);
+        self.finish();
+    }
+
+    /// Resets the mask to a rectangular one.
+    fn reset_rect(&mut self, width: LengthU32, height: LengthU32, rect: ScreenIntRect) {
+        self.width = width;
+        self.height = height;
+        self.rect = rect;
+        self.is_rect = true;
+        self.runs.clear();
+        self.rows.clear();
+    }
+
+    #[inline]
+    fn is_fully_clipped(&self) -> bool {
+        !self.is_rect && self.runs.is_empty()
+    }
+
+    /// Appends a run.
+    ///
+    /// Runs must be pushed from top to bottom and from left to right.
+    fn push_run(&mut self, x: u32, y: u32, width: LengthU32, alpha: AlphaU8) {
+        if alpha == ALPHA_U8_TRANSPARENT {
+            return;
+        }
+
+        // Close all the previous rows.
+        let row = (y - self.rect.y()) as usize;
+        debug_assert!(row + 1 >= self.rows.len());
+        while self.rows.len() < row + 1 {
+            self.rows.push(self.runs.len() as u32);
+        }
+
+        let has_row_runs = self.runs.len() > self.rows[row] as usize;
+        if let Some(last) = self.runs.last_mut() {
+            if has_row_runs {
+                debug_assert!(last.x + last.width.get() <= x);
+                if last.x + last.width.get() == x && last.alpha == alpha {
+                    // Cannot overflow, since the run is inside the mask.
+                    last.width = LengthU32::new(last.width.get() + width.get()).unwrap();
+                    return;
+                }
+            }
+        }
+
+        self.runs.push(ClipRun { x, width, alpha });
+    }
+
+    /// Appends a dense row of coverage values starting at `x`.
+    fn push_row(&mut self, x: u32, y: u32, values: &[u8]) {
+        let mut i = 0;
+        while i < values.len() {
+            let alpha = values[i];
+            let start = i;
+            while i < values.len() && values[i] == alpha {
+                i += 1;
+            }
+
+            // Cannot fail, since we have at least one value.
+            let width = LengthU32::new((i - start) as u32).unwrap();
+            self.push_run(x + start as u32, y, width, alpha);
+        }
+    }
+
+    /// Closes the remaining rows. Must be called after all runs were pushed.
+    fn finish(&mut self) {
+        while self.rows.len() < self.rect.height() as usize + 1 {
+            self.rows.push(self.runs.len() as u32);
+        }
+    }
+
+    #[inline]
+    fn row_runs(&self, y: u32) -> &[ClipRun] {
+        if y < self.rect.top() || y >= self.rect.bottom() {
+            return &[];
+        }
+
+        let row = (y - self.rect.y()) as usize;
+        &self.runs[self.rows[row] as usize..self.rows[row + 1] as usize]
+    }
+
+    /// Writes coverage values of the specified row part into `values`.
+    ///
+    /// Anything outside the mask is fully clipped.
+    fn decode_row(&self, x: u32, y: u32, values: &mut [u8]) {
+        values.iter_mut().for_each(|v| *v = ALPHA_U8_TRANSPARENT);
+
+        let right = x + values.len() as u32;
+        if self.is_rect {
+            if y >= self.rect.top() && y < self.rect.bottom() {
+                for i in self.rect.left().max(x)..self.rect.right().min(right) {
+                    values[(i - x) as usize] = ALPHA_U8_OPAQUE;
+                }
+            }
+
+            return;
+        }
+
+        for run in self.row_runs(y) {
+            let left = run.x.max(x);
+            let r = (run.x + run.width.get()).min(right);
+            for i in left..r {
+                values[(i - x) as usize] = run.alpha;
+            }
+        }
+    }
+
+    /// Splits a horizontal span into fully visible and partially covered segments.
+    ///
+    /// Fully clipped parts are skipped.
+    /// Coverage of the span is written into `values`, with `values[0]` corresponding to `x`.
+    pub(crate) fn segments(
+        &self,
+        x: u32,
+        y: u32,
+        width: LengthU32,
+        segments: &mut Vec<ClipSegment>,
+        values: &mut Vec<u8>,
+    ) {
+        segments.clear();
+        values.clear();
+        values.resize(width.get() as usize, ALPHA_U8_TRANSPARENT);
+
+        let right = x + width.get();
+        for run in self.row_runs(y) {
+            let left = run.x.max(x);
+            let r = (run.x + run.width.get()).min(right);
+            if left >= r {
+                continue;
+            }
+
+            let masked = run.alpha != ALPHA_U8_OPAQUE;
+            if masked {
+                for v in &mut values[(left - x) as usize..(r - x) as usize] {
+                    *v = run.alpha;
+                }
+            }
+
+            if let Some(last) = segments.last_mut() {
+                if last.x + last.width.get() == left && last.masked == masked {
+                    // Cannot overflow, since the segment is inside the span.
+                    last.width = LengthU32::new(last.width.get() + r - left).unwrap();
+                    continue;
+                }
+            }
+
+            // Cannot fail, since `left < r`.
+            let width = LengthU32::new(r - left).unwrap();
+            segments.push(ClipSegment { x: left, width, masked });
+        }
+    }
+}
+
+/// A clip mask combination operation.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ClipOp {
+    /// Keeps only the area covered by both masks.
+    Intersect,
+    /// Keeps the area covered by any of the masks.
+    Union,
+    /// Removes the area covered by the other mask.
+    Difference,
+    /// Keeps the area covered by only one of the masks.
+    Xor,
+}
+
+impl ClipOp {
+    #[inline]
+    fn apply(self, a: u8, b: u8) -> u8 {
+        let ab = premultiply_u8(a, b);
+        match self {
+            ClipOp::Intersect => ab,
+            // `ab` is always less or equal to `a` and `b`, so we cannot underflow.
+            ClipOp::Union => (u16::from(a) + u16::from(b) - u16::from(ab)) as u8,
+            ClipOp::Difference => a - ab,
+            ClipOp::Xor => (a - ab) + (b - ab),
+        }
+    }
+}
+
+
+/// A clip mask source type.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MaskType {
+    /// Uses the alpha channel.
+    Alpha,
+    /// Uses the luminance multiplied by alpha.
+    ///
+    /// The same as SVG `mask-type="luminance"`, but without a color space conversion.
+    Luminance,
+}
+
+impl MaskType {
+    #[inline]
+    fn coverage(self, c: PremultipliedColorU8) -> u8 {
+        match self {
+            MaskType::Alpha => c.alpha(),
+            // Premultiplied colors are already multiplied by alpha.
+            MaskType::Luminance => {
+                let luma = 0.2125 * f32::from(c.red())
+                    + 0.7154 * f32::from(c.green())
+                    + 0.0721 * f32::from(c.blue());
+                (luma + 0.5) as u8
+            }
         }
     }
 }
@@ -36,6 +289,12 @@ impl ClipMaskData {
 ///
 /// Unlike Skia, we're using just a simple 8bit alpha mask.
 /// It's way slower, but times easier to implement.
+///
+/// Only the mask's non-empty bounding box is stored,
+/// so small clips on large pixmaps are cheap.
+///
+/// Pixel-aligned rectangular clips, set via `set_rect`, do not store a mask at all
+/// and are as fast as no clipping.
 #[derive(Clone, Debug)]
 pub struct ClipMask {
     pub(crate) mask: ClipMaskData,
@@ -45,9 +304,12 @@ impl Default for ClipMask {
     fn default() -> Self {
         ClipMask {
             mask: ClipMaskData {
-                data: Vec::new(),
+                runs: Vec::new(),
+                rows: Vec::new(),
                 width: LENGTH_U32_ONE,
                 height: LENGTH_U32_ONE,
+                rect: ScreenIntRect::from_xywh_safe(0, 0, LENGTH_U32_ONE, LENGTH_U32_ONE),
+                is_rect: false,
             }
         }
     }
@@ -59,16 +321,142 @@ impl ClipMask {
         ClipMask::default()
     }
 
+    /// Creates a new mask from a pixmap.
+    ///
+    /// Allows using any rendered image as a soft mask, like SVG `<mask>`.
+    pub fn from_pixmap(pixmap: PixmapRef, mask_type: MaskType) -> Self {
+        // Find the non-empty area first, to store only it.
+        let mut left = u32::MAX;
+        let mut top = u32::MAX;
+        let mut right = 0;
+        let mut bottom = 0;
+        for (y, row) in pixmap.rows().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                if mask_type.coverage(*c) != 0 {
+                    let (x, y) = (x as u32, y as u32);
+                    left = left.min(x);
+                    top = top.min(y);
+                    right = right.max(x + 1);
+                    bottom = bottom.max(y + 1);
+                }
+            }
+        }
+
+        // A pixmap always has a non-zero size.
+        let width = LengthU32::new(pixmap.width()).unwrap();
+        let height = LengthU32::new(pixmap.height()).unwrap();
+
+        let mut mask = ClipMask::new();
+        if left >= right {
+            mask.mask.reset_clipped(width, height);
+            return mask;
+        }
+
+        // Cannot fail, since the area is inside the pixmap.
+        let rect = ScreenIntRect::from_xywh(left, top, right - left, bottom - top).unwrap();
+
+        mask.mask.reset(width, height, rect);
+        let mut values = vec![0; rect.width() as usize];
+        for y in rect.top()..rect.bottom() {
+            // Rows are always in bounds.
+            let row = &pixmap.row(y).unwrap()[rect.x() as usize..];
+            for (v, c) in values.iter_mut().zip(row) {
+                *v = mask_type.coverage(*c);
+            }
+
+            mask.mask.push_row(rect.x(), y, &values);
+        }
+        mask.mask.finish();
+
+        mask
+    }
+
     /// Checks that mask is empty.
     pub fn is_empty(&self) -> bool {
-        self.mask.data.is_empty()
+        self.mask.rows.is_empty() && !self.mask.is_rect
+    }
+
+    /// Returns the per-pixel mask.
+    ///
+    /// Returns `None` for rectangular clips, which are handled by the clip rect alone.
+    pub(crate) fn pixel_mask(&self) -> Option<&ClipMaskData> {
+        if self.mask.is_rect {
+            None
+        } else {
+            Some(&self.mask)
+        }
+    }
+
+    /// Sets the current clipping rectangle.
+    ///
+    /// Not additive. Overwrites the previous data.
+    ///
+    /// When the transformed rectangle is pixel-aligned or `anti_alias` is disabled,
+    /// no mask is allocated and clipping has no rendering cost.
+    /// Otherwise, as well as for transforms with a skew, this is the same as `set_path`.
+    ///
+    /// Returns `None` when the size is zero, the transform is invalid
+    /// or the rectangle is outside the mask.
+    pub fn set_rect(
+        &mut self,
+        width: u32,
+        height: u32,
+        rect: Rect,
+        anti_alias: bool,
+        transform: Transform,
+    ) -> Option<()> {
+        if transform.has_skew() {
+            let path = PathBuilder::from_rect(rect);
+            return self.set_path(width, height, &path, FillRule::Winding, anti_alias, transform);
+        }
+
+        let width = NonZeroU32::new(width)?;
+        let height = NonZeroU32::new(height)?;
+
+        let mut points = [
+            Point::from_xy(rect.left(), rect.top()),
+            Point::from_xy(rect.right(), rect.bottom()),
+        ];
+        transform.map_points(&mut points);
+        let rect = Rect::from_points(&points)?;
+
+        let is_aligned = rect.left() == rect.left().floor() && rect.top() == rect.top().floor()
+            && rect.right() == rect.right().floor() && rect.bottom() == rect.bottom().floor();
+        if anti_alias && !is_aligned {
+            let path = PathBuilder::from_rect(rect);
+            return self.set_path(width.get(), height.get(), &path, FillRule::Winding,
+                                 anti_alias, Transform::identity());
+        }
+
+        // Pixel centers must be inside the rect, just like during a non-AA path filling.
+        let int_rect = IntRect::from_ltrb(
+            i32::saturate_floor(rect.left() + 0.5),
+            i32::saturate_floor(rect.top() + 0.5),
+            i32::saturate_floor(rect.right() + 0.5),
+            i32::saturate_floor(rect.bottom() + 0.5),
+        );
+
+        let full_rect = ScreenIntRect::from_xywh_safe(0, 0, width, height);
+        match int_rect.and_then(|r| r.intersect(&full_rect.to_int_rect()))
+            .and_then(|r| r.to_screen_int_rect())
+        {
+            Some(rect) => {
+                self.mask.reset_rect(width, height, rect);
+                Some(())
+            }
+            None => {
+                self.mask.reset_clipped(width, height);
+                None
+            }
+        }
     }
 
     /// Sets the current clipping path.
     ///
     /// Not additive. Overwrites the previous data.
     ///
-    /// Path must be transformed beforehand.
+    /// Returns `None` when the size is zero, the transform is invalid
+    /// or the path is outside the mask.
     pub fn set_path(
         &mut self,
         width: u32,
@@ -76,34 +464,371 @@ impl ClipMask {
         path: &Path,
         fill_rule: FillRule,
         anti_alias: bool,
+        transform: Transform,
     ) -> Option<()> {
         let width = NonZeroU32::new(width)?;
         let height = NonZeroU32::new(height)?;
 
-        self.mask.width = width;
-        self.mask.height = height;
+        let transformed_path;
+        let path = if transform.is_identity() {
+            path
+        } else {
+            transformed_path = path.clone().transform(transform)?;
+            &transformed_path
+        };
 
-        // Reuse the existing allocation.
-        self.mask.data.clear();
-        self.mask.data.resize((width.get() * height.get()) as usize, 0);
+        // Allocate only the region actually covered by the path.
+        // Anti-aliasing can touch an extra pixel on each side.
+        let full_rect = ScreenIntRect::from_xywh_safe(0, 0, width, height);
+        let rect = path.bounds().round_out().make_outset(1, 1)
+            .and_then(|r| r.intersect(&full_rect.to_int_rect()))
+            .and_then(|r| r.to_screen_int_rect());
+        let rect = match rect {
+            Some(rect) => rect,
+            None => {
+                self.mask.reset_clipped(width, height);
+                return None;
+            }
+        };
 
-        let clip = ScreenIntRect::from_xywh_safe(0, 0, width, height);
+        self.mask.reset(width, height, rect);
 
-        if anti_alias {
+        let result = if anti_alias {
             let mut builder = ClipBuilderAA(&mut self.mask);
-            crate::scan::path_aa::fill_path(path, fill_rule, &clip, &mut builder)
+            crate::scan::path_aa::fill_path(path, fill_rule, &rect, &mut builder)
         } else {
             let mut builder = ClipBuilder(&mut self.mask);
-            crate::scan::path::fill_path(path, fill_rule, &clip, &mut builder)
+            crate::scan::path::fill_path(path, fill_rule, &rect, &mut builder)
+        };
+
+        self.mask.finish();
+        result
+    }
+
+    /// Sets the current clip to a region.
+    ///
+    /// Not additive. Overwrites the previous data.
+    ///
+    /// A single rectangle region doesn't allocate a mask, just like `set_rect`.
+    ///
+    /// Returns `None` when the size is zero or the region is outside the mask.
+    pub fn set_region(&mut self, width: u32, height: u32, region: &Region) -> Option<()> {
+        let width = NonZeroU32::new(width)?;
+        let height = NonZeroU32::new(height)?;
+
+        let full_rect = ScreenIntRect::from_xywh_safe(0, 0, width, height).to_int_rect();
+        let rect = region.bounds()
+            .and_then(|r| r.intersect(&full_rect))
+            .and_then(|r| r.to_screen_int_rect());
+        let rect = match rect {
+            Some(rect) => rect,
+            None => {
+                self.mask.reset_clipped(width, height);
+                return None;
+            }
+        };
+
+        if region.is_rect() {
+            self.mask.reset_rect(width, height, rect);
+            return Some(());
+        }
+
+        self.mask.reset(width, height, rect);
+
+        // Region rects are banded, so we can push them band by band.
+        let mut band = Vec::new();
+        let mut rects = region.rects().iter().peekable();
+        while let Some(first) = rects.next() {
+            band.clear();
+            band.push(*first);
+            while let Some(r) = rects.peek() {
+                if r.top() != first.top() {
+                    break;
+                }
+
+                band.push(**r);
+                rects.next();
+            }
+
+            for y in first.top().max(0)..first.bottom().min(rect.bottom() as i32) {
+                for r in &band {
+                    let left = r.left().max(0);
+                    let right = r.right().min(rect.right() as i32);
+                    if let Some(width) = LengthU32::new(right.saturating_sub(left).max(0) as u32) {
+                        self.mask.push_run(left as u32, y as u32, width, ALPHA_U8_OPAQUE);
+                    }
+                }
+            }
+        }
+
+        self.mask.finish();
+        Some(())
+    }
+
+    /// Sets the current clip to pixels passing a stencil test.
+    ///
+    /// Not additive. Overwrites the previous data.
+    ///
+    /// A pixel is visible when `func` passes for `reference` and a stencil value.
+    ///
+    /// This is a one-time conversion, which has to be repeated each time the stencil changes.
+    /// Use `Paint::stencil_test` to test against the current stencil values during drawing instead.
+    pub fn set_stencil(&mut self, stencil: &Stencil, func: StencilFunc, reference: u8) {
+        // Stencil's size is never zero.
+        let width = LengthU32::new(stencil.width()).unwrap();
+        let height = LengthU32::new(stencil.height()).unwrap();
+
+        let rect = ScreenIntRect::from_xywh_safe(0, 0, width, height);
+        self.mask.reset(width, height, rect);
+
+        let mut values = vec![0; width.get() as usize];
+        for (y, row) in stencil.data().chunks(width.get() as usize).enumerate() {
+            for (v, s) in values.iter_mut().zip(row) {
+                *v = if func.test(reference, *s) { ALPHA_U8_OPAQUE } else { ALPHA_U8_TRANSPARENT };
+            }
+
+            self.mask.push_row(0, y as u32, &values);
+        }
+
+        self.mask.finish();
+    }
+
+    /// Intersects the current clipping path with a new one.
+    ///
+    /// Anti-aliased coverage is multiplied.
+    ///
+    /// Returns `None` when the mask is empty.
+    pub fn intersect_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        anti_alias: bool,
+        transform: Transform,
+    ) -> Option<()> {
+        self.combine_path(path, fill_rule, anti_alias, transform, ClipOp::Intersect)
+    }
+
+    /// Adds a new clipping path to the current one.
+    ///
+    /// Returns `None` when the mask is empty.
+    pub fn union_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        anti_alias: bool,
+        transform: Transform,
+    ) -> Option<()> {
+        self.combine_path(path, fill_rule, anti_alias, transform, ClipOp::Union)
+    }
+
+    /// Subtracts a new clipping path from the current one.
+    ///
+    /// Returns `None` when the mask is empty.
+    pub fn difference_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        anti_alias: bool,
+        transform: Transform,
+    ) -> Option<()> {
+        self.combine_path(path, fill_rule, anti_alias, transform, ClipOp::Difference)
+    }
+
+    /// Keeps only the area covered by either the current or a new clipping path, but not both.
+    ///
+    /// Returns `None` when the mask is empty.
+    pub fn xor_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        anti_alias: bool,
+        transform: Transform,
+    ) -> Option<()> {
+        self.combine_path(path, fill_rule, anti_alias, transform, ClipOp::Xor)
+    }
+
+    fn combine_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        anti_alias: bool,
+        transform: Transform,
+        op: ClipOp,
+    ) -> Option<()> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // `set_path` fails only when the path is outside the mask,
+        // in which case we still have a valid, fully clipped mask.
+        let mut other = ClipMask::new();
+        let _ = other.set_path(self.mask.width.get(), self.mask.height.get(),
+                               path, fill_rule, anti_alias, transform);
+        if other.is_empty() {
+            // An invalid transform.
+            return None;
         }
+
+        self.combine(&other, op)
+    }
+
+    /// Combines the current mask with another one.
+    ///
+    /// Returns `None` when any of the masks is empty or when they have different sizes.
+    pub fn combine(&mut self, other: &ClipMask, op: ClipOp) -> Option<()> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+
+        if self.mask.width != other.mask.width || self.mask.height != other.mask.height {
+            return None;
+        }
+
+        let a = &self.mask;
+        let b = &other.mask;
+
+        // An intersection of two rectangles is still a rectangle.
+        if op == ClipOp::Intersect && a.is_rect && b.is_rect {
+            let (width, height) = (a.width, a.height);
+            match a.rect.to_int_rect().intersect(&b.rect.to_int_rect())
+                .and_then(|r| r.to_screen_int_rect())
+            {
+                Some(rect) => self.mask.reset_rect(width, height, rect),
+                None => self.mask.reset_clipped(width, height),
+            }
+
+            return Some(());
+        }
+
+        // The region that can be non-zero after the operation.
+        let rect = match op {
+            ClipOp::Intersect => {
+                if a.is_fully_clipped() || b.is_fully_clipped() {
+                    None
+                } else {
+                    a.rect.to_int_rect().intersect(&b.rect.to_int_rect())
+                        .and_then(|r| r.to_screen_int_rect())
+                }
+            }
+            ClipOp::Union | ClipOp::Xor => {
+                if a.is_fully_clipped() {
+                    Some(b.rect)
+                } else if b.is_fully_clipped() {
+                    Some(a.rect)
+                } else {
+                    let left = a.rect.left().min(b.rect.left());
+                    let top = a.rect.top().min(b.rect.top());
+                    let right = a.rect.right().max(b.rect.right());
+                    let bottom = a.rect.bottom().max(b.rect.bottom());
+                    ScreenIntRect::from_xywh(left, top, right - left, bottom - top)
+                }
+            }
+            ClipOp::Difference => Some(a.rect),
+        };
+
+        let mut result = ClipMaskData {
+            runs: Vec::new(),
+            rows: Vec::new(),
+            width: a.width,
+            height: a.height,
+            rect: a.rect,
+            is_rect: false,
+        };
+
+        match rect {
+            Some(rect) => {
+                result.reset(a.width, a.height, rect);
+
+                let mut values_a = vec![0; rect.width() as usize];
+                let mut values_b = vec![0; rect.width() as usize];
+                for y in rect.top()..rect.bottom() {
+                    a.decode_row(rect.x(), y, &mut values_a);
+                    b.decode_row(rect.x(), y, &mut values_b);
+                    for (va, vb) in values_a.iter_mut().zip(values_b.iter()) {
+                        *va = op.apply(*va, *vb);
+                    }
+
+                    result.push_row(rect.x(), y, &values_a);
+                }
+
+                result.finish();
+            }
+            None => result.reset_clipped(a.width, a.height),
+        }
+
+        self.mask = result;
+        Some(())
+    }
+
+    /// Creates a mask for a part of the current one.
+    ///
+    /// The new mask has the size of `rect`, with `rect`'s top-left corner at (0, 0).
+    /// Anything outside the current mask is fully clipped.
+    ///
+    /// Allows using clip masks created for a pixmap inside its `draw_layer`.
+    ///
+    /// Returns `None` when the mask is empty.
+    pub fn crop(&self, rect: IntRect) -> Option<ClipMask> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let width = LengthU32::new(rect.width())?;
+        let height = LengthU32::new(rect.height())?;
+        let a = &self.mask;
+
+        let mut mask = ClipMask::new();
+
+        // The stored region, in the new mask coordinates.
+        let stored = if a.is_fully_clipped() {
+            None
+        } else {
+            a.rect.to_int_rect().intersect(&rect)
+        };
+        let stored = match stored {
+            // Cannot fail, since the intersection is inside `rect`.
+            Some(r) => ScreenIntRect::from_xywh(
+                (r.x() - rect.x()) as u32,
+                (r.y() - rect.y()) as u32,
+                r.width(),
+                r.height(),
+            )?,
+            None => {
+                mask.mask.reset_clipped(width, height);
+                return Some(mask);
+            }
+        };
+
+        if a.is_rect {
+            mask.mask.reset_rect(width, height, stored);
+            return Some(mask);
+        }
+
+        mask.mask.reset(width, height, stored);
+        let mut values = vec![0; stored.width() as usize];
+        for y in stored.top()..stored.bottom() {
+            // Cannot overflow, since the region is inside the current mask.
+            let src_x = (stored.x() as i32 + rect.x()) as u32;
+            let src_y = (y as i32 + rect.y()) as u32;
+            a.decode_row(src_x, src_y, &mut values);
+            mask.mask.push_row(stored.x(), y, &values);
+        }
+        mask.mask.finish();
+
+        Some(mask)
     }
 
     /// Clears the mask.
     ///
     /// Internal memory buffer is not deallocated.
     pub fn clear(&mut self) {
-        // Clear the mask, but keep the allocation.
-        self.mask.data.clear();
+        // Reset the mask to the default state, but keep the allocation.
+        self.mask.runs.clear();
+        self.mask.rows.clear();
+        self.mask.width = LENGTH_U32_ONE;
+        self.mask.height = LENGTH_U32_ONE;
+        self.mask.rect = ScreenIntRect::from_xywh_safe(0, 0, LENGTH_U32_ONE, LENGTH_U32_ONE);
+        self.mask.is_rect = false;
     }
 }
 
@@ -112,10 +837,7 @@ struct ClipBuilder<'a>(&'a mut ClipMaskData);
 
 impl Blitter for ClipBuilder<'_> {
     fn blit_h(&mut self, x: u32, y: u32, width: LengthU32) {
-        let offset = (y * self.0.width.get() + x) as usize;
-        for i in 0..width.get() as usize {
-            self.0.data[offset + i] = 255;
-        }
+        self.0.push_run(x, y, width, ALPHA_U8_OPAQUE);
     }
 }
 
@@ -124,10 +846,7 @@ struct ClipBuilderAA<'a>(&'a mut ClipMaskData);
 
 impl Blitter for ClipBuilderAA<'_> {
     fn blit_h(&mut self, x: u32, y: u32, width: LengthU32) {
-        let offset = (y * self.0.width.get() + x) as usize;
-        for i in 0..width.get() as usize {
-            self.0.data[offset + i] = 255;
-        }
+        self.0.push_run(x, y, width, ALPHA_U8_OPAQUE);
     }
 
     fn blit_anti_h(&mut self, mut x: u32, y: u32, aa: &mut [AlphaU8], runs: &mut [AlphaRun]) {
@@ -136,19 +855,7 @@ impl Blitter for ClipBuilderAA<'_> {
         let mut run_opt = runs[0];
         while let Some(run) = run_opt {
             let width = LengthU32::from(run);
-
-            match aa[aa_offset] {
-                ALPHA_U8_TRANSPARENT => {}
-                ALPHA_U8_OPAQUE => {
-                    self.blit_h(x, y, width);
-                }
-                alpha => {
-                    let offset = (y * self.0.width.get() + x) as usize;
-                    for i in 0..width.get() as usize {
-                        self.0.data[offset + i] = alpha;
-                    }
-                }
-            }
+            self.0.push_run(x, y, width, aa[aa_offset]);
 
             x += width.get();
             run_offset += usize::from(run.get());
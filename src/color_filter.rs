@@ -0,0 +1,229 @@
+// Copyright 2006 The Android Open Source Project
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{ColorLut3D, LutInterpolation, PixmapRef, PixmapMut};
+
+use crate::pipeline::{self, RasterPipelineBuilder};
+use crate::scalar::Scalar;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
+/// A color filter.
+///
+/// Applied to the shader's output before blending.
+/// Operates on unpremultiplied colors, just like SVG filters.
+/// The result is always clamped to the 0..=1 range.
+///
+/// Color filters are always rendered using the high precision pipeline.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ColorFilter {
+    /// A 4x5 color matrix.
+    ///
+    /// Row-major, with the last column being a translation in the 0..=1 range.
+    /// The same as SVG `feColorMatrix` with `type="matrix"`.
+    Matrix([f32; 20]),
+    /// Converts colors to alpha using luminance coefficients.
+    ///
+    /// The same as SVG `feColorMatrix` with `type="luminanceToAlpha"`.
+    LuminanceToAlpha,
+    /// Per-channel transfer functions.
+    ///
+    /// The same as SVG `feComponentTransfer`.
+    ComponentTransfer {
+        /// A red channel function.
+        red: TransferFunction,
+        /// A green channel function.
+        green: TransferFunction,
+        /// A blue channel function.
+        blue: TransferFunction,
+        /// An alpha channel function.
+        alpha: TransferFunction,
+    },
+    /// A 3D LUT color grading.
+    ///
+    /// Alpha is preserved.
+    Lut3D {
+        /// A lookup table.
+        lut: Arc<ColorLut3D>,
+        /// An interpolation method.
+        interpolation: LutInterpolation,
+    },
+}
+
+/// A component transfer function.
+///
+/// All functions operate on values in the 0..=1 range.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TransferFunction {
+    /// Keeps the value unchanged.
+    Identity,
+    /// Linear interpolation between table values.
+    ///
+    /// An empty table acts like `Identity`.
+    Table(Vec<f32>),
+    /// A step function using table values.
+    ///
+    /// An empty table acts like `Identity`.
+    Discrete(Vec<f32>),
+    /// `slope * C + intercept`
+    #[allow(missing_docs)]
+    Linear { slope: f32, intercept: f32 },
+    /// `amplitude * pow(C, exponent) + offset`
+    #[allow(missing_docs)]
+    Gamma { amplitude: f32, exponent: f32, offset: f32 },
+}
+
+impl ColorFilter {
+    /// Creates a saturation matrix.
+    ///
+    /// `0` is fully desaturated and `1` keeps colors unchanged.
+    ///
+    /// The same as SVG `feColorMatrix` with `type="saturate"`.
+    pub fn saturate(v: f32) -> Self {
+        ColorFilter::Matrix([
+            0.213 + 0.787 * v, 0.715 - 0.715 * v, 0.072 - 0.072 * v, 0.0, 0.0,
+            0.213 - 0.213 * v, 0.715 + 0.285 * v, 0.072 - 0.072 * v, 0.0, 0.0,
+            0.213 - 0.213 * v, 0.715 - 0.715 * v, 0.072 + 0.928 * v, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ])
+    }
+
+    /// Creates a hue rotation matrix.
+    ///
+    /// The same as SVG `feColorMatrix` with `type="hueRotate"`.
+    pub fn hue_rotate(degrees: f32) -> Self {
+        let angle = degrees.to_radians();
+        let sin = angle.sin();
+        let cos = angle.cos();
+        ColorFilter::Matrix([
+            0.213 + cos * 0.787 - sin * 0.213,
+            0.715 - cos * 0.715 - sin * 0.715,
+            0.072 - cos * 0.072 + sin * 0.928,
+            0.0, 0.0,
+            0.213 - cos * 0.213 + sin * 0.143,
+            0.715 + cos * 0.285 + sin * 0.140,
+            0.072 - cos * 0.072 - sin * 0.283,
+            0.0, 0.0,
+            0.213 - cos * 0.213 - sin * 0.787,
+            0.715 - cos * 0.715 + sin * 0.715,
+            0.072 + cos * 0.928 + sin * 0.072,
+            0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ])
+    }
+
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder) {
+        p.push(pipeline::Stage::Unpremultiply);
+
+        match self {
+            ColorFilter::Matrix(ref matrix) => {
+                p.ctx.color_matrix = *matrix;
+                p.push(pipeline::Stage::ColorMatrix);
+            }
+            ColorFilter::LuminanceToAlpha => {
+                p.ctx.color_matrix = [
+                    0.0, 0.0, 0.0, 0.0, 0.0,
+                    0.0, 0.0, 0.0, 0.0, 0.0,
+                    0.0, 0.0, 0.0, 0.0, 0.0,
+                    0.2125, 0.7154, 0.0721, 0.0, 0.0,
+                ];
+                p.push(pipeline::Stage::ColorMatrix);
+            }
+            ColorFilter::ComponentTransfer { ref red, ref green, ref blue, ref alpha } => {
+                // Like Skia, component transfer is implemented via 8-bit lookup tables.
+                p.ctx.byte_tables = Some(Box::new([
+                    red.to_byte_table(),
+                    green.to_byte_table(),
+                    blue.to_byte_table(),
+                    alpha.to_byte_table(),
+                ]));
+                p.push(pipeline::Stage::ByteTables);
+            }
+            ColorFilter::Lut3D { ref lut, interpolation } => {
+                p.ctx.lut = Some(lut.clone());
+                p.push(match interpolation {
+                    LutInterpolation::Trilinear => pipeline::Stage::Lut3DTrilinear,
+                    LutInterpolation::Tetrahedral => pipeline::Stage::Lut3DTetrahedral,
+                });
+            }
+        }
+
+        p.push(pipeline::Stage::Clamp0);
+        p.push(pipeline::Stage::ClampA);
+        p.push(pipeline::Stage::Premultiply);
+    }
+
+    pub(crate) fn apply(&self, pixmap: &mut PixmapMut) {
+        let mut p = RasterPipelineBuilder::new();
+        p.push(pipeline::Stage::LoadDestination);
+        p.push(pipeline::Stage::MoveDestinationToSource);
+        self.push_stages(&mut p);
+        p.push(pipeline::Stage::Store);
+        let mut p = p.compile();
+
+        let rect = pixmap.size().to_screen_int_rect(0, 0);
+        // Just a dummy one.
+        let pixmap_src = PixmapRef::from_bytes(&[0, 0, 0, 0], 1, 1).unwrap();
+        p.run(
+            &rect,
+            pipeline::AAMaskCtx::default(),
+            pipeline::ClipMaskCtx::default(),
+            pixmap_src,
+            pixmap,
+        );
+    }
+}
+
+impl TransferFunction {
+    fn apply(&self, c: f32) -> f32 {
+        match self {
+            TransferFunction::Identity => c,
+            TransferFunction::Table(ref values) => {
+                if values.is_empty() {
+                    return c;
+                }
+
+                let n = values.len() - 1;
+                let k = ((c * n as f32) as usize).min(n);
+                if k == n {
+                    return values[k];
+                }
+
+                let v1 = values[k];
+                let v2 = values[k + 1];
+                v1 + (c * n as f32 - k as f32) * (v2 - v1)
+            }
+            TransferFunction::Discrete(ref values) => {
+                if values.is_empty() {
+                    return c;
+                }
+
+                let n = values.len();
+                let k = ((c * n as f32) as usize).min(n - 1);
+                values[k]
+            }
+            TransferFunction::Linear { slope, intercept } => slope * c + intercept,
+            TransferFunction::Gamma { amplitude, exponent, offset } => {
+                amplitude * c.powf(*exponent) + offset
+            }
+        }
+    }
+
+    fn to_byte_table(&self) -> [u8; 256] {
+        let mut table = [0; 256];
+        for (i, v) in table.iter_mut().enumerate() {
+            let c = self.apply(i as f32 / 255.0);
+            *v = (c.bound(0.0, 1.0) * 255.0 + 0.5) as u8;
+        }
+
+        table
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::vec::Vec;
+
+use crate::scalar::Scalar;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
+// Just like Resolve.
+const MAX_LUT_SIZE: u32 = 256;
+
+/// A 3D LUT interpolation method.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LutInterpolation {
+    /// Interpolates between 8 neighbor entries.
+    Trilinear,
+    /// Interpolates between 4 neighbor entries.
+    ///
+    /// Usually more accurate for neutral colors.
+    Tetrahedral,
+}
+
+
+/// A 3D color lookup table.
+///
+/// Used by `ColorFilter::Lut3D` for color grading.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorLut3D {
+    size: u32,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    table: Vec<[f32; 3]>,
+}
+
+impl ColorLut3D {
+    /// Creates a new 3D LUT.
+    ///
+    /// `table` must contain `size^3` RGB entries, with the red index changing fastest,
+    /// just like in `.cube` files. The domain is 0..=1.
+    ///
+    /// Returns `None` when `size` is not in a 2..=256 range
+    /// or when `table` has an invalid length.
+    pub fn new(size: u32, table: Vec<[f32; 3]>) -> Option<Self> {
+        Self::new_with_domain(size, table, [0.0; 3], [1.0; 3])
+    }
+
+    fn new_with_domain(
+        size: u32,
+        table: Vec<[f32; 3]>,
+        domain_min: [f32; 3],
+        domain_max: [f32; 3],
+    ) -> Option<Self> {
+        if !(2..=MAX_LUT_SIZE).contains(&size) {
+            return None;
+        }
+
+        if table.len() != (size * size * size) as usize {
+            return None;
+        }
+
+        for i in 0..3 {
+            if !(domain_min[i] < domain_max[i]) {
+                return None;
+            }
+        }
+
+        Some(ColorLut3D {
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    /// Parses an Adobe/Resolve `.cube` file.
+    ///
+    /// Only 3D LUTs are supported.
+    ///
+    /// Returns `None` when the data is malformed or contains a 1D LUT.
+    pub fn from_cube(text: &str) -> Option<Self> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let keyword = parts.next()?;
+            match keyword {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => size = Some(parts.next()?.parse().ok()?),
+                "LUT_1D_SIZE" => return None,
+                "DOMAIN_MIN" => domain_min = parse_rgb(&mut parts)?,
+                "DOMAIN_MAX" => domain_max = parse_rgb(&mut parts)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let min: f32 = parts.next()?.parse().ok()?;
+                    let max: f32 = parts.next()?.parse().ok()?;
+                    domain_min = [min; 3];
+                    domain_max = [max; 3];
+                }
+                _ => {
+                    let r = keyword.parse().ok()?;
+                    let g = parts.next()?.parse().ok()?;
+                    let b = parts.next()?.parse().ok()?;
+                    table.push([r, g, b]);
+                }
+            }
+        }
+
+        Self::new_with_domain(size?, table, domain_min, domain_max)
+    }
+
+    /// Returns LUT's size along each axis.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    #[inline]
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        let size = self.size as usize;
+        self.table[r + g * size + b * size * size]
+    }
+
+    // Returns neighbor indices and fractions for each channel.
+    #[inline]
+    fn locate(&self, rgb: [f32; 3]) -> ([usize; 3], [usize; 3], [f32; 3]) {
+        let last = (self.size - 1) as f32;
+        let mut idx0 = [0; 3];
+        let mut idx1 = [0; 3];
+        let mut frac = [0.0; 3];
+        for i in 0..3 {
+            let t = (rgb[i] - self.domain_min[i]) / (self.domain_max[i] - self.domain_min[i]);
+            let v = t.bound(0.0, 1.0) * last;
+            let i0 = v.floor();
+            idx0[i] = i0 as usize;
+            idx1[i] = (idx0[i] + 1).min(self.size as usize - 1);
+            frac[i] = v - i0;
+        }
+
+        (idx0, idx1, frac)
+    }
+
+    pub(crate) fn sample_trilinear(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let ([r0, g0, b0], [r1, g1, b1], [fr, fg, fb]) = self.locate(rgb);
+
+        let c00 = lerp(self.entry(r0, g0, b0), self.entry(r1, g0, b0), fr);
+        let c10 = lerp(self.entry(r0, g1, b0), self.entry(r1, g1, b0), fr);
+        let c01 = lerp(self.entry(r0, g0, b1), self.entry(r1, g0, b1), fr);
+        let c11 = lerp(self.entry(r0, g1, b1), self.entry(r1, g1, b1), fr);
+
+        let c0 = lerp(c00, c10, fg);
+        let c1 = lerp(c01, c11, fg);
+
+        lerp(c0, c1, fb)
+    }
+
+    pub(crate) fn sample_tetrahedral(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let ([r0, g0, b0], [r1, g1, b1], [fr, fg, fb]) = self.locate(rgb);
+
+        let c000 = self.entry(r0, g0, b0);
+        let c111 = self.entry(r1, g1, b1);
+
+        // Select one of the 6 tetrahedra containing the point.
+        let (c1, c2, w1, w2, w3) = if fr > fg {
+            if fg > fb {
+                (self.entry(r1, g0, b0), self.entry(r1, g1, b0), fr, fg, fb)
+            } else if fr > fb {
+                (self.entry(r1, g0, b0), self.entry(r1, g0, b1), fr, fb, fg)
+            } else {
+                (self.entry(r0, g0, b1), self.entry(r1, g0, b1), fb, fr, fg)
+            }
+        } else if fb > fg {
+            (self.entry(r0, g0, b1), self.entry(r0, g1, b1), fb, fg, fr)
+        } else if fb > fr {
+            (self.entry(r0, g1, b0), self.entry(r0, g1, b1), fg, fb, fr)
+        } else {
+            (self.entry(r0, g1, b0), self.entry(r1, g1, b0), fg, fr, fb)
+        };
+
+        let mut out = [0.0; 3];
+        for i in 0..3 {
+            out[i] = c000[i]
+                + w1 * (c1[i] - c000[i])
+                + w2 * (c2[i] - c1[i])
+                + w3 * (c111[i] - c2[i]);
+        }
+
+        out
+    }
+}
+
+fn parse_rgb<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<[f32; 3]> {
+    Some([
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+    ])
+}
+
+#[inline]
+fn lerp(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
@@ -632,6 +632,14 @@ impl ScreenIntRect {
             self.bottom() >= other.bottom()
     }
 
+    /// Returns rect's size.
+    pub fn size(&self) -> IntSize {
+        IntSize {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
     /// Converts into a `IntRect`.
     pub fn to_int_rect(&self) -> IntRect {
         // Everything is already checked by constructors.
@@ -41,6 +41,8 @@ mod blend_mode;
 mod blitter;
 mod clip;
 mod color;
+mod color_filter;
+mod color_lut;
 mod dash;
 mod edge;
 mod edge_builder;
@@ -54,28 +56,38 @@ mod path64;
 mod path;
 mod path_builder;
 mod path_geometry;
+mod picture;
 mod pipeline;
 mod pixmap;
 mod painter; // Keep it under `pixmap` for a better order in the docs.
+mod region;
 mod scalar;
 mod scan;
 mod shaders;
+mod stencil;
 mod stroker;
 mod transform;
 mod wide;
 
-pub use blend_mode::BlendMode;
-pub use clip::ClipMask;
+pub use blend_mode::{BlendMode, Blender};
+pub use clip::{ClipMask, ClipOp, MaskType};
 pub use color::{ALPHA_U8_TRANSPARENT, ALPHA_U8_OPAQUE, ALPHA_TRANSPARENT, ALPHA_OPAQUE};
 pub use color::{Color, ColorU8, PremultipliedColor, PremultipliedColorU8};
+pub use color_filter::{ColorFilter, TransferFunction};
+pub use color_lut::{ColorLut3D, LutInterpolation};
 pub use dash::StrokeDash;
 pub use geom::{IntRect, Rect, Point};
 pub use painter::{Paint, FillRule};
 pub use path::{Path, PathSegment, PathSegmentsIter};
 pub use path_builder::PathBuilder;
+pub use picture::{Picture, PictureRecorder};
 pub use pixmap::{Pixmap, PixmapRef, PixmapMut, BYTES_PER_PIXEL};
-pub use shaders::{GradientStop, SpreadMode, FilterQuality, PixmapPaint};
-pub use shaders::{Shader, LinearGradient, RadialGradient, Pattern};
+pub use region::Region;
+pub use shaders::{GradientStop, SpreadMode, FilterQuality, CubicResampler, PixmapPaint};
+pub use shaders::{GradientInterpolation, InterpolationColorSpace, HueInterpolation};
+pub use shaders::{Shader, LinearGradient, RadialGradient, Pattern, PerlinNoise, PictureShader};
+pub use shaders::{CustomShader, CustomShaderFn, CUSTOM_SHADER_BATCH_SIZE};
+pub use stencil::{Stencil, StencilFunc, StencilOp, StencilTest};
 pub use stroker::{LineCap, LineJoin, Stroke};
 pub use transform::Transform;
 
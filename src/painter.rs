@@ -6,6 +6,8 @@
 
 use crate::*;
 
+use crate::floating_point::SaturateRound;
+use crate::geom::{IntSize, ScreenIntRect};
 use crate::pipeline::RasterPipelineBlitter;
 use crate::scalar::Scalar;
 use crate::scan;
@@ -47,11 +49,42 @@ pub struct Paint<'a> {
     /// Default: SourceOver
     pub blend_mode: BlendMode,
 
+    /// A custom blender.
+    ///
+    /// Overrides `blend_mode` when set.
+    ///
+    /// Default: None
+    pub blender: Option<Blender>,
+
+    /// Paint opacity.
+    ///
+    /// Applied to all shader types uniformly, after the `color_filter`.
+    /// Unlike `Shader::apply_opacity`, doesn't modify the shader.
+    ///
+    /// Must be in 0..=1 range.
+    ///
+    /// Default: 1.0
+    pub opacity: f32,
+
     /// Enables anti-aliased painting.
     ///
     /// Default: false
     pub anti_alias: bool,
 
+    /// A color filter applied to the shader's output.
+    ///
+    /// Default: None
+    pub color_filter: Option<ColorFilter>,
+
+    /// A stencil test.
+    ///
+    /// Applied in addition to a clip mask.
+    /// Unlike `ClipMask::set_stencil`, doesn't require any preprocessing,
+    /// therefore the stencil can be modified between draws.
+    ///
+    /// Default: None
+    pub stencil_test: Option<StencilTest<'a>>,
+
     /// Forces the high quality/precision rendering pipeline.
     ///
     /// `tiny-skia`, just like Skia, has two rendering pipelines:
@@ -76,7 +109,11 @@ impl Default for Paint<'_> {
         Paint {
             shader: Shader::SolidColor(Color::BLACK),
             blend_mode: BlendMode::default(),
+            blender: None,
+            opacity: 1.0,
             anti_alias: false,
+            color_filter: None,
+            stencil_test: None,
             force_hq_pipeline: false,
         }
     }
@@ -158,6 +195,55 @@ impl Pixmap {
     ) -> Option<()> {
         self.as_mut().draw_pixmap(x, y, pixmap, paint, transform, clip_mask)
     }
+
+    /// Draws a part of a `Pixmap` into a rectangle on top of the current `Pixmap`.
+    ///
+    /// See [`PixmapMut::draw_pixmap_rect`](struct.PixmapMut.html#method.draw_pixmap_rect) for details.
+    pub fn draw_pixmap_rect(
+        &mut self,
+        pixmap: PixmapRef,
+        src_rect: Rect,
+        dst_rect: Rect,
+        paint: &PixmapPaint,
+        transform: Transform,
+        clip_mask: Option<&ClipMask>,
+    ) -> Option<()> {
+        self.as_mut().draw_pixmap_rect(pixmap, src_rect, dst_rect, paint, transform, clip_mask)
+    }
+
+    /// Draws a `Picture` on top of the current `Pixmap`.
+    ///
+    /// See [`PixmapMut::draw_picture`](struct.PixmapMut.html#method.draw_picture) for details.
+    pub fn draw_picture(
+        &mut self,
+        picture: &Picture,
+        transform: Transform,
+        clip_mask: Option<&ClipMask>,
+    ) {
+        self.as_mut().draw_picture(picture, transform, clip_mask)
+    }
+
+    /// Applies a `ColorFilter` to the whole pixmap.
+    ///
+    /// See [`PixmapMut::apply_color_filter`](struct.PixmapMut.html#method.apply_color_filter) for details.
+    pub fn apply_color_filter(&mut self, filter: &ColorFilter) {
+        self.as_mut().apply_color_filter(filter)
+    }
+
+    /// Draws into an offscreen layer and then draws it on top of the current `Pixmap`.
+    ///
+    /// See [`PixmapMut::draw_layer`](struct.PixmapMut.html#method.draw_layer) for details.
+    pub fn draw_layer<F>(
+        &mut self,
+        bounds: Option<Rect>,
+        paint: &PixmapPaint,
+        clip_mask: Option<&ClipMask>,
+        draw: F,
+    ) -> Option<()>
+        where F: FnOnce(&mut PixmapMut, Transform, IntRect)
+    {
+        self.as_mut().draw_layer(bounds, paint, clip_mask, draw)
+    }
 }
 
 impl PixmapMut<'_> {
@@ -186,9 +272,9 @@ impl PixmapMut<'_> {
                 return None;
             }
 
-            let clip = self.size().to_screen_int_rect(0, 0);
+            let clip = clip_rect(self.size(), clip_mask)?;
 
-            let clip_mask = clip_mask.map(|mask| &mask.mask);
+            let clip_mask = clip_mask.and_then(|mask| mask.pixel_mask());
             let mut blitter = RasterPipelineBlitter::new(paint, clip_mask, self)?;
 
             if paint.anti_alias {
@@ -234,9 +320,9 @@ impl PixmapMut<'_> {
                 return None;
             }
 
-            let clip_rect = self.size().to_screen_int_rect(0, 0);
+            let clip_rect = clip_rect(self.size(), clip_mask)?;
 
-            let clip_mask = clip_mask.map(|mask| &mask.mask);
+            let clip_mask = clip_mask.and_then(|mask| mask.pixel_mask());
             let mut blitter = RasterPipelineBlitter::new(paint, clip_mask, self)?;
 
             if paint.anti_alias {
@@ -295,7 +381,7 @@ impl PixmapMut<'_> {
             let mut paint = paint.clone();
             if coverage == 1.0 {
                 // No changes to the `paint`.
-            } else if paint.blend_mode.should_pre_scale_coverage() {
+            } else if paint.blender.is_none() && paint.blend_mode.should_pre_scale_coverage() {
                 // This is the old technique, which we preserve for now so
                 // we don't change previous results (testing)
                 // the new way seems fine, its just (a tiny bit) different.
@@ -333,9 +419,9 @@ impl PixmapMut<'_> {
         line_cap: LineCap,
         clip_mask: Option<&ClipMask>,
     ) -> Option<()> {
-        let clip = self.size().to_screen_int_rect(0, 0);
+        let clip = clip_rect(self.size(), clip_mask)?;
 
-        let clip_mask = clip_mask.map(|mask| &mask.mask);
+        let clip_mask = clip_mask.and_then(|mask| mask.pixel_mask());
         let mut blitter = RasterPipelineBlitter::new(paint, clip_mask, self)?;
 
         if paint.anti_alias {
@@ -375,12 +461,155 @@ impl PixmapMut<'_> {
                 patt_transform,
             ),
             blend_mode: paint.blend_mode,
+            blender: None,
+            opacity: 1.0, // Already applied by the pattern.
             anti_alias: false, // Skia doesn't use it too.
+            color_filter: paint.color_filter.clone(),
+            stencil_test: None,
             force_hq_pipeline: false, // Pattern will use hq anyway.
         };
 
         self.fill_rect(rect, &paint, transform, clip_mask)
     }
+
+    /// Draws a part of a `Pixmap` into a rectangle on top of the current `Pixmap`.
+    ///
+    /// `src_rect` will be scaled to fit `dst_rect`.
+    /// When `src_rect` is partially outside the `pixmap`, `dst_rect` will be adjusted accordingly.
+    ///
+    /// Unless `PixmapPaint::strict` is set, filtering can sample
+    /// pixels just outside `src_rect`.
+    ///
+    /// Returns `None` when `src_rect` is outside the `pixmap`.
+    pub fn draw_pixmap_rect(
+        &mut self,
+        pixmap: PixmapRef,
+        src_rect: Rect,
+        dst_rect: Rect,
+        paint: &PixmapPaint,
+        transform: Transform,
+        clip_mask: Option<&ClipMask>,
+    ) -> Option<()> {
+        let sx = dst_rect.width() / src_rect.width();
+        let sy = dst_rect.height() / src_rect.height();
+        let patt_transform = Transform::from_row(
+            sx, 0.0, 0.0, sy,
+            dst_rect.x() - src_rect.x() * sx,
+            dst_rect.y() - src_rect.y() * sy,
+        );
+
+        // Like Skia, draw only the part of the `src_rect` that is inside the pixmap.
+        let src_rect = src_rect.intersect(&pixmap.size().to_int_rect(0, 0).to_rect())?;
+        let dst_rect = Rect::from_ltrb(
+            src_rect.left() * sx + patt_transform.tx,
+            src_rect.top() * sy + patt_transform.ty,
+            src_rect.right() * sx + patt_transform.tx,
+            src_rect.bottom() * sy + patt_transform.ty,
+        )?;
+
+        let mut quality = paint.quality;
+        if paint.strict && quality == FilterQuality::Trilinear {
+            // Mipmaps are generated from the whole pixmap,
+            // so they will include pixels outside of the `src_rect`.
+            quality = FilterQuality::Bilinear;
+        }
+
+        let mut shader = Pattern::new(
+            pixmap,
+            SpreadMode::Pad,
+            quality,
+            paint.opacity,
+            patt_transform,
+        );
+
+        if paint.strict {
+            if let Shader::Pattern(ref mut patt) = shader {
+                // Any pixel that is at least partially covered by `src_rect` can be sampled.
+                patt.subset = IntRect::from_ltrb(
+                    i32::saturate_floor(src_rect.left()),
+                    i32::saturate_floor(src_rect.top()),
+                    i32::saturate_ceil(src_rect.right()),
+                    i32::saturate_ceil(src_rect.bottom()),
+                )?.to_screen_int_rect();
+            }
+        }
+
+        let paint = Paint {
+            shader,
+            blend_mode: paint.blend_mode,
+            blender: None,
+            opacity: 1.0, // Already applied by the pattern.
+            anti_alias: false, // Skia doesn't use it too.
+            color_filter: paint.color_filter.clone(),
+            stencil_test: None,
+            force_hq_pipeline: false, // Pattern will use hq anyway.
+        };
+
+        self.fill_rect(dst_rect, &paint, transform, clip_mask)
+    }
+
+    /// Draws a `Picture` on top of the current `Pixmap`.
+    ///
+    /// Replays all recorded commands. `transform` is applied after
+    /// commands' own transforms.
+    pub fn draw_picture(
+        &mut self,
+        picture: &Picture,
+        transform: Transform,
+        clip_mask: Option<&ClipMask>,
+    ) {
+        picture.play(self, transform, clip_mask)
+    }
+
+    /// Applies a `ColorFilter` to the whole pixmap.
+    ///
+    /// Unlike drawing with `Paint::color_filter`, modifies existing pixels in-place.
+    pub fn apply_color_filter(&mut self, filter: &ColorFilter) {
+        filter.apply(self)
+    }
+
+    /// Draws into an offscreen layer and then draws it on top of the current `Pixmap`.
+    ///
+    /// Allows applying opacity, blending mode and color filter to a group of drawings
+    /// as a whole. Like SVG `<g opacity="0.5">`.
+    ///
+    /// The layer is allocated using `bounds` (in pixmap coordinates) clipped to the pixmap.
+    /// The whole pixmap is used when `bounds` is not set.
+    ///
+    /// `draw` will be called with a transparent layer, a transform that must be
+    /// post-concatenated to drawing transforms, so they can use the current pixmap coordinates,
+    /// and the layer bounds in the current pixmap coordinates.
+    /// Since the layer is usually smaller than the current pixmap, clip masks have to be
+    /// converted using `ClipMask::crop` with the layer bounds to be used inside it.
+    /// `clip_mask` is applied when drawing the layer.
+    ///
+    /// Returns `None` when `bounds` are outside the pixmap. `draw` will not be called in this case.
+    pub fn draw_layer<F>(
+        &mut self,
+        bounds: Option<Rect>,
+        paint: &PixmapPaint,
+        clip_mask: Option<&ClipMask>,
+        draw: F,
+    ) -> Option<()>
+        where F: FnOnce(&mut PixmapMut, Transform, IntRect)
+    {
+        let pixmap_rect = self.size().to_int_rect(0, 0);
+        let rect = match bounds {
+            Some(bounds) => IntRect::from_ltrb(
+                i32::saturate_floor(bounds.left()),
+                i32::saturate_floor(bounds.top()),
+                i32::saturate_ceil(bounds.right()),
+                i32::saturate_ceil(bounds.bottom()),
+            )?.intersect(&pixmap_rect)?,
+            None => pixmap_rect,
+        };
+
+        let mut layer = Pixmap::new(rect.width(), rect.height())?;
+        let ts = Transform::from_translate(-rect.x() as f32, -rect.y() as f32);
+        draw(&mut layer.as_mut(), ts, rect);
+
+        self.draw_pixmap(rect.x(), rect.y(), layer.as_ref(), paint, Transform::identity(), clip_mask)
+    }
 }
 
 fn treat_as_hairline(paint: &Paint, stroke: &Stroke, mut ts: Transform) -> Option<f32> {
@@ -421,3 +650,25 @@ fn treat_as_hairline(paint: &Paint, stroke: &Stroke, mut ts: Transform) -> Optio
 
     None
 }
+
+// Only the clip mask's stored region can be painted.
+// For rectangular masks, this is the only clipping we have.
+fn clip_rect(size: IntSize, clip_mask: Option<&ClipMask>) -> Option<ScreenIntRect> {
+    let rect = size.to_screen_int_rect(0, 0);
+    match clip_mask {
+        Some(mask) => {
+            // An empty mask has no data to clip with.
+            if mask.is_empty() {
+                return None;
+            }
+
+            // Make sure that `clip_mask` has the same size as `pixmap`.
+            if mask.mask.width.get() != size.width() || mask.mask.height.get() != size.height() {
+                return None;
+            }
+
+            rect.to_int_rect().intersect(&mask.mask.rect.to_int_rect())?.to_screen_int_rect()
+        }
+        None => Some(rect),
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2007 The Android Open Source Project
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::vec::Vec;
+
+use crate::{Path, Paint, FillRule, Stroke, Rect, Transform, PixmapRef, PixmapMut, PixmapPaint, ClipMask};
+
+/// A recorded list of drawing commands.
+///
+/// Can be created using `PictureRecorder` and drawn via `PixmapMut::draw_picture`
+/// or `PictureShader`.
+///
+/// Essentially a `SkPicture`.
+#[derive(Clone, Debug)]
+pub struct Picture<'a> {
+    commands: Vec<Command<'a>>,
+    bounds: Rect,
+}
+
+#[derive(Clone, Debug)]
+enum Command<'a> {
+    FillPath {
+        path: Path,
+        paint: Paint<'a>,
+        fill_rule: FillRule,
+        transform: Transform,
+    },
+    StrokePath {
+        path: Path,
+        paint: Paint<'a>,
+        stroke: Stroke,
+        transform: Transform,
+    },
+    FillRect {
+        rect: Rect,
+        paint: Paint<'a>,
+        transform: Transform,
+    },
+    DrawPixmap {
+        x: i32,
+        y: i32,
+        pixmap: PixmapRef<'a>,
+        paint: PixmapPaint,
+        transform: Transform,
+    },
+}
+
+impl<'a> Picture<'a> {
+    /// Returns picture's bounds.
+    ///
+    /// The same as passed to `PictureRecorder::new`.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Checks that the picture has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub(crate) fn play(&self, pixmap: &mut PixmapMut, ts: Transform, clip_mask: Option<&ClipMask>) {
+        // A command will fail only when there is nothing to draw, so we can ignore it.
+        for command in &self.commands {
+            match command {
+                Command::FillPath { ref path, ref paint, fill_rule, transform } => {
+                    let transform = transform.post_concat(ts);
+                    pixmap.fill_path(path, paint, *fill_rule, transform, clip_mask);
+                }
+                Command::StrokePath { ref path, ref paint, ref stroke, transform } => {
+                    let transform = transform.post_concat(ts);
+                    pixmap.stroke_path(path, paint, stroke, transform, clip_mask);
+                }
+                Command::FillRect { rect, ref paint, transform } => {
+                    let transform = transform.post_concat(ts);
+                    pixmap.fill_rect(*rect, paint, transform, clip_mask);
+                }
+                Command::DrawPixmap { x, y, pixmap: src, ref paint, transform } => {
+                    let transform = transform.post_concat(ts);
+                    pixmap.draw_pixmap(*x, *y, *src, paint, transform, clip_mask);
+                }
+            }
+        }
+    }
+}
+
+
+/// A picture recorder.
+///
+/// Unlike `Pixmap`, doesn't draw anything, but stores drawing commands
+/// to be replayed later.
+///
+/// Essentially a `SkPictureRecorder`.
+#[derive(Debug)]
+pub struct PictureRecorder<'a> {
+    commands: Vec<Command<'a>>,
+    bounds: Rect,
+}
+
+impl<'a> PictureRecorder<'a> {
+    /// Creates a new recorder.
+    ///
+    /// `bounds` is the area of interest. It's used as a default tile by `PictureShader`.
+    pub fn new(bounds: Rect) -> Self {
+        PictureRecorder {
+            commands: Vec::new(),
+            bounds,
+        }
+    }
+
+    /// Records a path filling.
+    ///
+    /// See [`PixmapMut::fill_path`](struct.PixmapMut.html#method.fill_path) for details.
+    pub fn fill_path(&mut self, path: &Path, paint: &Paint<'a>, fill_rule: FillRule, transform: Transform) {
+        self.commands.push(Command::FillPath {
+            path: path.clone(),
+            paint: paint.clone(),
+            fill_rule,
+            transform,
+        });
+    }
+
+    /// Records a path stroking.
+    ///
+    /// See [`PixmapMut::stroke_path`](struct.PixmapMut.html#method.stroke_path) for details.
+    pub fn stroke_path(&mut self, path: &Path, paint: &Paint<'a>, stroke: &Stroke, transform: Transform) {
+        self.commands.push(Command::StrokePath {
+            path: path.clone(),
+            paint: paint.clone(),
+            stroke: stroke.clone(),
+            transform,
+        });
+    }
+
+    /// Records a rectangle filling.
+    ///
+    /// See [`PixmapMut::fill_rect`](struct.PixmapMut.html#method.fill_rect) for details.
+    pub fn fill_rect(&mut self, rect: Rect, paint: &Paint<'a>, transform: Transform) {
+        self.commands.push(Command::FillRect {
+            rect,
+            paint: paint.clone(),
+            transform,
+        });
+    }
+
+    /// Records a pixmap drawing.
+    ///
+    /// See [`PixmapMut::draw_pixmap`](struct.PixmapMut.html#method.draw_pixmap) for details.
+    pub fn draw_pixmap(
+        &mut self,
+        x: i32,
+        y: i32,
+        pixmap: PixmapRef<'a>,
+        paint: &PixmapPaint,
+        transform: Transform,
+    ) {
+        self.commands.push(Command::DrawPixmap {
+            x,
+            y,
+            pixmap,
+            paint: paint.clone(),
+            transform,
+        });
+    }
+
+    /// Finishes the recording.
+    pub fn finish(self) -> Picture<'a> {
+        Picture {
+            commands: self.commands,
+            bounds: self.bounds,
+        }
+    }
+}
@@ -4,26 +4,41 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
-use crate::{Paint, BlendMode, LengthU32, PixmapMut, PremultipliedColorU8, Shader, PixmapRef};
+use alloc::vec::Vec;
+
+use crate::{Paint, BlendMode, Blender, LengthU32, PixmapMut, PremultipliedColorU8, Shader, PixmapRef};
+use crate::StencilTest;
 use crate::{ALPHA_U8_OPAQUE, ALPHA_U8_TRANSPARENT};
 
 use crate::alpha_runs::AlphaRun;
 use crate::blitter::{Blitter, Mask};
-use crate::clip::ClipMaskData;
+use crate::clip::{ClipMaskData, ClipSegment};
 use crate::color::AlphaU8;
 use crate::geom::ScreenIntRect;
 use crate::math::LENGTH_U32_ONE;
 use crate::pipeline::{self, RasterPipeline, RasterPipelineBuilder};
+use crate::scalar::Scalar;
 
 
 pub struct RasterPipelineBlitter<'a, 'b: 'a> {
     clip_mask: Option<&'a ClipMaskData>,
+    stencil_test: Option<StencilTest<'a>>,
     pixmap_src: PixmapRef<'a>,
     pixmap: &'a mut PixmapMut<'b>,
     memset2d_color: Option<PremultipliedColorU8>,
-    blit_anti_h_rp: RasterPipeline,
-    blit_rect_rp: RasterPipeline,
-    blit_mask_rp: RasterPipeline,
+    // Used for fully visible pixels.
+    unclipped: Pipelines<'a>,
+    // Used for partially clipped pixels. Only when we have a clip mask.
+    clipped: Option<Pipelines<'a>>,
+    clip_segments: Vec<ClipSegment>,
+    clip_values: Vec<u8>,
+    stencil_segments: Vec<ClipSegment>,
+}
+
+struct Pipelines<'a> {
+    blit_anti_h_rp: RasterPipeline<'a>,
+    blit_rect_rp: RasterPipeline<'a>,
+    blit_mask_rp: RasterPipeline<'a>,
 }
 
 impl<'a, 'b: 'a> RasterPipelineBlitter<'a, 'b> {
@@ -39,58 +54,191 @@ impl<'a, 'b: 'a> RasterPipelineBlitter<'a, 'b> {
             }
         }
 
+        // The same for `stencil_test`.
+        if let Some(ref test) = paint.stencil_test {
+            if test.stencil.width() != pixmap.width() || test.stencil.height() != pixmap.height() {
+                return None;
+            }
+        }
+
+        let opacity = paint.opacity.bound(0.0, 1.0);
+
+        // A color filter can change the shader's color and opacity.
+        let is_opaque = paint.shader.is_opaque() && paint.color_filter.is_none() && opacity == 1.0;
+        let is_solid_color = paint.is_solid_color() && paint.color_filter.is_none();
+
+        // A custom blender cannot be simplified, therefore all the shortcuts below
+        // are applied only to blending modes.
+        let has_blender = paint.blender.is_some();
+
         // Fast-reject.
         // This is basically SkInterpretXfermode().
         match paint.blend_mode {
             // `Destination` keep the pixmap unchanged. Nothing to do here.
-            BlendMode::Destination => return None,
-            BlendMode::DestinationIn if paint.shader.is_opaque() && paint.is_solid_color()
-                => return None,
+            BlendMode::Destination if !has_blender => return None,
+            BlendMode::DestinationIn if !has_blender && is_opaque && is_solid_color => return None,
             _ => {}
         }
 
         // We can strength-reduce SourceOver into Source when opaque.
+        // Partially clipped pixels are rendered using a separate pipeline,
+        // since Source doesn't preserve clipped out pixels.
         let mut blend_mode = paint.blend_mode;
-        if paint.shader.is_opaque() && blend_mode == BlendMode::SourceOver {
+        if is_opaque && blend_mode == BlendMode::SourceOver {
             blend_mode = BlendMode::Source;
         }
 
         // When we're drawing a constant color in Source mode, we can sometimes just memset.
         let mut memset2d_color = None;
-        if paint.is_solid_color() && blend_mode == BlendMode::Source {
+        if !has_blender && is_solid_color && blend_mode == BlendMode::Source {
             // Unlike Skia, our shader cannot be constant.
             // Therefore there is no need to run a raster pipeline to get shader's color.
-            if let Shader::SolidColor(ref color) = paint.shader {
+            if let Shader::SolidColor(mut color) = paint.shader {
+                color.apply_opacity(opacity);
                 memset2d_color = Some(color.premultiply().to_color_u8());
             }
         };
 
         // Clear is just a transparent color memset.
-        if blend_mode == BlendMode::Clear && !paint.anti_alias {
+        if !has_blender && blend_mode == BlendMode::Clear && !paint.anti_alias {
             blend_mode = BlendMode::Source;
             memset2d_color = Some(PremultipliedColorU8::TRANSPARENT);
         }
 
+        let unclipped = Pipelines::new(paint, Blend::new(blend_mode, paint.blender), opacity, false);
+        let clipped = clip_mask.map(|_| {
+            Pipelines::new(paint, Blend::new(paint.blend_mode, paint.blender), opacity, true)
+        });
+
+        let pixmap_src = paint.shader.pixmap_src()
+            // Just a dummy one.
+            .unwrap_or_else(|| PixmapRef::from_bytes(&[0, 0, 0, 0], 1, 1).unwrap());
+
+        Some(RasterPipelineBlitter {
+            clip_mask,
+            stencil_test: paint.stencil_test,
+            pixmap_src,
+            pixmap,
+            memset2d_color,
+            unclipped,
+            clipped,
+            clip_segments: Vec::new(),
+            clip_values: Vec::new(),
+            stencil_segments: Vec::new(),
+        })
+    }
+
+    fn is_clipped(&self) -> bool {
+        self.clip_mask.is_some() || self.stencil_test.is_some()
+    }
+
+    // Splits a span into visible segments using the clip mask and the stencil test.
+    //
+    // Clip mask values are written into `clip_values`, with `clip_values[0]` corresponding to `x`.
+    fn clip_span(&mut self, x: u32, y: u32, width: LengthU32) {
+        match self.clip_mask {
+            Some(mask) => mask.segments(x, y, width, &mut self.clip_segments, &mut self.clip_values),
+            None => {
+                self.clip_segments.clear();
+                self.clip_segments.push(ClipSegment { x, width, masked: false });
+            }
+        }
+
+        if let Some(ref test) = self.stencil_test {
+            self.stencil_segments.clear();
+            for segment in &self.clip_segments {
+                test.split_segment(y, *segment, &mut self.stencil_segments);
+            }
+
+            core::mem::swap(&mut self.clip_segments, &mut self.stencil_segments);
+        }
+    }
+
+    fn blit_rect_unclipped(&mut self, rect: &ScreenIntRect) {
+        if let Some(c) = self.memset2d_color {
+            for y in 0..rect.height() {
+                let start = self.pixmap.offset(rect.x() as usize, (rect.y() + y) as usize);
+                let end = start + rect.width() as usize;
+                self.pixmap.pixels_mut()[start..end].iter_mut().for_each(|p| *p = c);
+            }
+
+            return;
+        }
+
+        self.unclipped.blit_rect_rp.run(
+            rect,
+            pipeline::AAMaskCtx::default(),
+            pipeline::ClipMaskCtx::default(),
+            self.pixmap_src,
+            self.pixmap,
+        );
+    }
+
+    fn blit_anti_h_run(&mut self, x: u32, y: u32, width: LengthU32, alpha: AlphaU8) {
+        let coverage = alpha as f32 * (1.0 / 255.0);
+
+        if !self.is_clipped() {
+            self.unclipped.blit_anti_h_rp.ctx.current_coverage = coverage;
+
+            let rect = ScreenIntRect::from_xywh_safe(x, y, width, LENGTH_U32_ONE);
+            self.unclipped.blit_anti_h_rp.run(
+                &rect,
+                pipeline::AAMaskCtx::default(),
+                pipeline::ClipMaskCtx::default(),
+                self.pixmap_src,
+                self.pixmap,
+            );
+            return;
+        }
+
+        self.clip_span(x, y, width);
+        for segment in &self.clip_segments {
+            let rect = ScreenIntRect::from_xywh_safe(segment.x, y, segment.width, LENGTH_U32_ONE);
+            let (p, clip_mask_ctx) = match self.clipped {
+                Some(ref mut clipped) if segment.masked => {
+                    (clipped, clip_values_ctx(&self.clip_values, x, y, width))
+                }
+                _ => (&mut self.unclipped, pipeline::ClipMaskCtx::default()),
+            };
+
+            p.blit_anti_h_rp.ctx.current_coverage = coverage;
+            p.blit_anti_h_rp.run(
+                &rect,
+                pipeline::AAMaskCtx::default(),
+                clip_mask_ctx,
+                self.pixmap_src,
+                self.pixmap,
+            );
+        }
+    }
+}
+
+impl<'a> Pipelines<'a> {
+    fn new(paint: &Paint<'a>, blend: Blend, opacity: f32, clip_mask: bool) -> Self {
         let blit_anti_h_rp = {
             let mut p = RasterPipelineBuilder::new();
             p.set_force_hq_pipeline(paint.force_hq_pipeline);
             paint.shader.push_stages(&mut p);
+            if let Some(ref filter) = paint.color_filter {
+                filter.push_stages(&mut p);
+            }
 
-            if clip_mask.is_some() {
+            if opacity != 1.0 {
+                p.ctx.opacity = opacity;
+                p.push(pipeline::Stage::Opacity);
+            }
+
+            if clip_mask {
                 p.push(pipeline::Stage::MaskU8);
             }
 
-            if blend_mode.should_pre_scale_coverage() {
+            if blend.should_pre_scale_coverage() {
                 p.push(pipeline::Stage::Scale1Float);
                 p.push(pipeline::Stage::LoadDestination);
-                if let Some(blend_stage) = blend_mode.to_stage() {
-                    p.push(blend_stage);
-                }
+                blend.push_stages(&mut p);
             } else {
                 p.push(pipeline::Stage::LoadDestination);
-                if let Some(blend_stage) = blend_mode.to_stage() {
-                    p.push(blend_stage);
-                }
+                blend.push_stages(&mut p);
 
                 p.push(pipeline::Stage::Lerp1Float);
             }
@@ -104,20 +252,26 @@ impl<'a, 'b: 'a> RasterPipelineBlitter<'a, 'b> {
             let mut p = RasterPipelineBuilder::new();
             p.set_force_hq_pipeline(paint.force_hq_pipeline);
             paint.shader.push_stages(&mut p);
+            if let Some(ref filter) = paint.color_filter {
+                filter.push_stages(&mut p);
+            }
+
+            if opacity != 1.0 {
+                p.ctx.opacity = opacity;
+                p.push(pipeline::Stage::Opacity);
+            }
 
-            if clip_mask.is_some() {
+            if clip_mask {
                 p.push(pipeline::Stage::MaskU8);
             }
 
-            if blend_mode == BlendMode::SourceOver && clip_mask.is_none() {
+            if blend == Blend::Mode(BlendMode::SourceOver) && !clip_mask {
                 // TODO: ignore when dither_rate is non-zero
                 p.push(pipeline::Stage::SourceOverRgba);
             } else {
-                if blend_mode != BlendMode::Source {
+                if blend != Blend::Mode(BlendMode::Source) {
                     p.push(pipeline::Stage::LoadDestination);
-                    if let Some(blend_stage) = blend_mode.to_stage() {
-                        p.push(blend_stage);
-                    }
+                    blend.push_stages(&mut p);
                 }
 
                 p.push(pipeline::Stage::Store);
@@ -130,22 +284,26 @@ impl<'a, 'b: 'a> RasterPipelineBlitter<'a, 'b> {
             let mut p = RasterPipelineBuilder::new();
             p.set_force_hq_pipeline(paint.force_hq_pipeline);
             paint.shader.push_stages(&mut p);
+            if let Some(ref filter) = paint.color_filter {
+                filter.push_stages(&mut p);
+            }
+
+            if opacity != 1.0 {
+                p.ctx.opacity = opacity;
+                p.push(pipeline::Stage::Opacity);
+            }
 
-            if clip_mask.is_some() {
+            if clip_mask {
                 p.push(pipeline::Stage::MaskU8);
             }
 
-            if blend_mode.should_pre_scale_coverage() {
+            if blend.should_pre_scale_coverage() {
                 p.push(pipeline::Stage::ScaleU8);
                 p.push(pipeline::Stage::LoadDestination);
-                if let Some(blend_stage) = blend_mode.to_stage() {
-                    p.push(blend_stage);
-                }
+                blend.push_stages(&mut p);
             } else {
                 p.push(pipeline::Stage::LoadDestination);
-                if let Some(blend_stage) = blend_mode.to_stage() {
-                    p.push(blend_stage);
-                }
+                blend.push_stages(&mut p);
 
                 p.push(pipeline::Stage::LerpU8);
             }
@@ -155,21 +313,51 @@ impl<'a, 'b: 'a> RasterPipelineBlitter<'a, 'b> {
             p.compile()
         };
 
-        let pixmap_src = match paint.shader {
-            Shader::Pattern(ref patt) => patt.pixmap,
-            // Just a dummy one.
-            _ => PixmapRef::from_bytes(&[0, 0, 0, 0], 1, 1).unwrap(),
-        };
-
-        Some(RasterPipelineBlitter {
-            clip_mask,
-            pixmap_src,
-            pixmap,
-            memset2d_color,
+        Pipelines {
             blit_anti_h_rp,
             blit_rect_rp,
             blit_mask_rp,
-        })
+        }
+    }
+}
+
+// Either a blending mode or a custom blender, which overrides it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Blend {
+    Mode(BlendMode),
+    Blender(Blender),
+}
+
+impl Blend {
+    fn new(blend_mode: BlendMode, blender: Option<Blender>) -> Self {
+        match blender {
+            Some(blender) => Blend::Blender(blender),
+            None => Blend::Mode(blend_mode),
+        }
+    }
+
+    fn should_pre_scale_coverage(self) -> bool {
+        match self {
+            Blend::Mode(mode) => mode.should_pre_scale_coverage(),
+            Blend::Blender(_) => false,
+        }
+    }
+
+    fn push_stages(self, p: &mut RasterPipelineBuilder) {
+        match self {
+            Blend::Mode(mode) => mode.push_stages(p),
+            Blend::Blender(blender) => blender.push_stages(p),
+        }
+    }
+}
+
+// Clip mask values of a single span, as returned by `ClipMaskData::segments`.
+fn clip_values_ctx(values: &[u8], x: u32, y: u32, width: LengthU32) -> pipeline::ClipMaskCtx<'_> {
+    pipeline::ClipMaskCtx {
+        data: values,
+        stride: width,
+        x,
+        y,
     }
 }
 
@@ -180,8 +368,6 @@ impl Blitter for RasterPipelineBlitter<'_, '_> {
     }
 
     fn blit_anti_h(&mut self, mut x: u32, y: u32, aa: &mut [AlphaU8], runs: &mut [AlphaRun]) {
-        let clip_mask_ctx = self.clip_mask.map(|c| c.clip_mask_ctx()).unwrap_or_default();
-
         let mut aa_offset = 0;
         let mut run_offset = 0;
         let mut run_opt = runs[0];
@@ -194,16 +380,7 @@ impl Blitter for RasterPipelineBlitter<'_, '_> {
                     self.blit_h(x, y, width);
                 }
                 alpha => {
-                    self.blit_anti_h_rp.ctx.current_coverage = alpha as f32 * (1.0 / 255.0);
-
-                    let rect = ScreenIntRect::from_xywh_safe(x, y, width, LENGTH_U32_ONE);
-                    self.blit_anti_h_rp.run(
-                        &rect,
-                        pipeline::AAMaskCtx::default(),
-                        clip_mask_ctx,
-                        self.pixmap_src,
-                        self.pixmap,
-                    );
+                    self.blit_anti_h_run(x, y, width, alpha);
                 }
             }
 
@@ -251,25 +428,33 @@ impl Blitter for RasterPipelineBlitter<'_, '_> {
     }
 
     fn blit_rect(&mut self, rect: &ScreenIntRect) {
-        if let Some(c) = self.memset2d_color {
-            for y in 0..rect.height() {
-                let start = self.pixmap.offset(rect.x() as usize, (rect.y() + y) as usize);
-                let end = start + rect.width() as usize;
-                self.pixmap.pixels_mut()[start..end].iter_mut().for_each(|p| *p = c);
-            }
-
+        if !self.is_clipped() {
+            self.blit_rect_unclipped(rect);
             return;
         }
 
-        let clip_mask_ctx = self.clip_mask.map(|c| c.clip_mask_ctx()).unwrap_or_default();
-
-        self.blit_rect_rp.run(
-            rect,
-            pipeline::AAMaskCtx::default(),
-            clip_mask_ctx,
-            self.pixmap_src,
-            self.pixmap,
-        );
+        // Fully visible and partially clipped pixels are rendered separately,
+        // so we have to process the rect row by row.
+        for y in rect.top()..rect.bottom() {
+            self.clip_span(rect.x(), y, rect.width_safe());
+
+            for i in 0..self.clip_segments.len() {
+                let segment = self.clip_segments[i];
+                let r = ScreenIntRect::from_xywh_safe(segment.x, y, segment.width, LENGTH_U32_ONE);
+                match self.clipped {
+                    Some(ref mut clipped) if segment.masked => {
+                        clipped.blit_rect_rp.run(
+                            &r,
+                            pipeline::AAMaskCtx::default(),
+                            clip_values_ctx(&self.clip_values, rect.x(), y, rect.width_safe()),
+                            self.pixmap_src,
+                            self.pixmap,
+                        );
+                    }
+                    _ => self.blit_rect_unclipped(&r),
+                }
+            }
+        }
     }
 
     fn blit_mask(&mut self, mask: &Mask, clip: &ScreenIntRect) {
@@ -279,14 +464,37 @@ impl Blitter for RasterPipelineBlitter<'_, '_> {
             shift: (mask.bounds.left() + mask.bounds.top() * mask.row_bytes) as usize,
         };
 
-        let clip_mask_ctx = self.clip_mask.map(|c| c.clip_mask_ctx()).unwrap_or_default();
+        if !self.is_clipped() {
+            self.unclipped.blit_mask_rp.run(
+                clip,
+                aa_mask_ctx,
+                pipeline::ClipMaskCtx::default(),
+                self.pixmap_src,
+                self.pixmap,
+            );
+            return;
+        }
 
-        self.blit_mask_rp.run(
-            clip,
-            aa_mask_ctx,
-            clip_mask_ctx,
-            self.pixmap_src,
-            self.pixmap,
-        );
+        for y in clip.top()..clip.bottom() {
+            self.clip_span(clip.x(), y, clip.width_safe());
+
+            for segment in &self.clip_segments {
+                let r = ScreenIntRect::from_xywh_safe(segment.x, y, segment.width, LENGTH_U32_ONE);
+                let (p, clip_mask_ctx) = match self.clipped {
+                    Some(ref mut clipped) if segment.masked => {
+                        (clipped, clip_values_ctx(&self.clip_values, clip.x(), y, clip.width_safe()))
+                    }
+                    _ => (&mut self.unclipped, pipeline::ClipMaskCtx::default()),
+                };
+
+                p.blit_mask_rp.run(
+                    &r,
+                    aa_mask_ctx,
+                    clip_mask_ctx,
+                    self.pixmap_src,
+                    self.pixmap,
+                );
+            }
+        }
     }
 }
@@ -18,18 +18,22 @@ and we're using a manual implementation.
 use crate::{PremultipliedColorU8, SpreadMode, PixmapMut, PixmapRef};
 
 use crate::geom::ScreenIntRect;
+use crate::scalar::Scalar;
 use crate::wide::{f32x8, i32x8, u32x8};
 
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
 pub const STAGE_WIDTH: usize = 8;
 
 pub type StageFn = fn(p: &mut Pipeline);
 
-pub struct Pipeline<'a, 'b: 'a> {
+pub struct Pipeline<'a, 'b: 'a, 'c: 'a> {
     index: usize,
     functions: &'a [StageFn],
     pixmap_src: PixmapRef<'a>,
     pixmap_dst: &'a mut PixmapMut<'b>,
-    ctx: &'a mut super::Context, // TODO: remove mut
+    ctx: &'a mut super::Context<'c>, // TODO: remove mut
     clip_mask_ctx: super::ClipMaskCtx<'a>,
     mask_ctx: super::AAMaskCtx,
     r: f32x8,
@@ -45,7 +49,7 @@ pub struct Pipeline<'a, 'b: 'a> {
     dy: usize,
 }
 
-impl Pipeline<'_, '_> {
+impl Pipeline<'_, '_, '_> {
     #[inline(always)]
     fn next_stage(&mut self) {
         let next: fn(&mut Self) = self.functions[self.index];
@@ -61,6 +65,8 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     clamp_0,
     clamp_a,
     premultiply,
+    unpremultiply,
+    unpremultiply_polar,
     uniform_color,
     seed_shader,
     load_dst,
@@ -100,10 +106,14 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     luminosity,
     source_over_rgba,
     transform,
-    reflect,
-    repeat,
+    reflect_x,
+    reflect_y,
+    repeat_x,
+    repeat_y,
     bilinear,
     bicubic,
+    cubic,
+    lanczos3,
     pad_x1,
     reflect_x1,
     repeat_x1,
@@ -113,8 +123,33 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     xy_to_2pt_conical_focal_on_circle,
     xy_to_2pt_conical_well_behaved,
     xy_to_2pt_conical_greater,
+    xy_to_2pt_conical_smaller,
+    xy_to_2pt_conical_strip,
+    alter_2pt_conical_radial,
+    alter_2pt_conical_compensate_focal,
+    alter_2pt_conical_unswap,
+    mask_2pt_conical_nan,
     mask_2pt_conical_degenerates,
     apply_vector_mask,
+    oklch_to_oklab,
+    oklab_to_linear_srgb,
+    linear_to_srgb,
+    gradient_lut,
+    compose_shaders,
+    lerp_shaders,
+    perlin_noise,
+    decal_x1,
+    decal_x,
+    decal_y,
+    decal_x_and_y,
+    check_decal_mask,
+    custom,
+    color_matrix,
+    byte_tables,
+    lut_3d_trilinear,
+    lut_3d_tetrahedral,
+    arithmetic,
+    opacity,
 ];
 
 pub fn fn_ptr(f: StageFn) -> *const () {
@@ -178,6 +213,204 @@ pub fn start(
     }
 }
 
+// Evaluates a shader pipeline for the current pixels.
+fn run_shader(
+    shader: &mut super::ShaderPipeline,
+    dx: usize,
+    dy: usize,
+    tail: usize,
+    pixmap_dst: &mut PixmapMut,
+) -> [f32x8; 4] {
+    let mut p = Pipeline {
+        index: 0,
+        functions: &shader.functions,
+        pixmap_src: shader.pixmap_src.as_ref(),
+        pixmap_dst,
+        clip_mask_ctx: super::ClipMaskCtx::default(),
+        mask_ctx: super::AAMaskCtx::default(),
+        ctx: &mut shader.ctx,
+        r: f32x8::default(),
+        g: f32x8::default(),
+        b: f32x8::default(),
+        a: f32x8::default(),
+        dr: f32x8::default(),
+        dg: f32x8::default(),
+        db: f32x8::default(),
+        da: f32x8::default(),
+        tail,
+        dx,
+        dy,
+    };
+
+    p.next_stage();
+
+    [p.r, p.g, p.b, p.a]
+}
+
+fn compose_shaders(p: &mut Pipeline) {
+    if let Some(ref mut shader) = p.ctx.compose.dst {
+        let [r, g, b, a] = run_shader(shader, p.dx, p.dy, p.tail, p.pixmap_dst);
+        p.dr = r;
+        p.dg = g;
+        p.db = b;
+        p.da = a;
+    }
+
+    if let Some(ref mut shader) = p.ctx.compose.src {
+        let [r, g, b, a] = run_shader(shader, p.dx, p.dy, p.tail, p.pixmap_dst);
+        p.r = r;
+        p.g = g;
+        p.b = b;
+        p.a = a;
+    }
+
+    p.next_stage();
+}
+
+fn lerp_shaders(p: &mut Pipeline) {
+    let t = f32x8::splat(p.ctx.compose.t);
+    p.r = lerp(p.dr, p.r, t);
+    p.g = lerp(p.dg, p.g, t);
+    p.b = lerp(p.db, p.b, t);
+    p.a = lerp(p.da, p.a, t);
+
+    p.next_stage();
+}
+
+fn perlin_noise(p: &mut Pipeline) {
+    let x: [f32; 8] = p.r.into();
+    let y: [f32; 8] = p.g.into();
+    let mut r = [0.0; 8];
+    let mut g = [0.0; 8];
+    let mut b = [0.0; 8];
+    let mut a = [0.0; 8];
+    for i in 0..STAGE_WIDTH {
+        let c = p.ctx.perlin_noise.turbulence(x[i], y[i]);
+        r[i] = c[0];
+        g[i] = c[1];
+        b[i] = c[2];
+        a[i] = c[3];
+    }
+
+    p.r = f32x8::from(r);
+    p.g = f32x8::from(g);
+    p.b = f32x8::from(b);
+    p.a = f32x8::from(a);
+
+    p.next_stage();
+}
+
+fn custom(p: &mut Pipeline) {
+    let x: [f32; STAGE_WIDTH] = p.r.into();
+    let y: [f32; STAGE_WIDTH] = p.g.into();
+    let mut colors = [crate::Color::TRANSPARENT.premultiply(); STAGE_WIDTH];
+    if let Some(ref function) = p.ctx.custom.function {
+        function.shade(&x, &y, &mut colors);
+    }
+
+    let mut r = [0.0; STAGE_WIDTH];
+    let mut g = [0.0; STAGE_WIDTH];
+    let mut b = [0.0; STAGE_WIDTH];
+    let mut a = [0.0; STAGE_WIDTH];
+    for i in 0..STAGE_WIDTH {
+        r[i] = colors[i].red();
+        g[i] = colors[i].green();
+        b[i] = colors[i].blue();
+        a[i] = colors[i].alpha();
+    }
+
+    let opacity = f32x8::splat(p.ctx.custom.opacity);
+    p.r = f32x8::from(r) * opacity;
+    p.g = f32x8::from(g) * opacity;
+    p.b = f32x8::from(b) * opacity;
+    p.a = f32x8::from(a) * opacity;
+
+    p.next_stage();
+}
+
+fn color_matrix(p: &mut Pipeline) {
+    let m = &p.ctx.color_matrix;
+    let r = p.r;
+    let g = p.g;
+    let b = p.b;
+    let a = p.a;
+
+    p.r = f32x8::splat(m[0]) * r + f32x8::splat(m[1]) * g + f32x8::splat(m[2]) * b
+        + f32x8::splat(m[3]) * a + f32x8::splat(m[4]);
+    p.g = f32x8::splat(m[5]) * r + f32x8::splat(m[6]) * g + f32x8::splat(m[7]) * b
+        + f32x8::splat(m[8]) * a + f32x8::splat(m[9]);
+    p.b = f32x8::splat(m[10]) * r + f32x8::splat(m[11]) * g + f32x8::splat(m[12]) * b
+        + f32x8::splat(m[13]) * a + f32x8::splat(m[14]);
+    p.a = f32x8::splat(m[15]) * r + f32x8::splat(m[16]) * g + f32x8::splat(m[17]) * b
+        + f32x8::splat(m[18]) * a + f32x8::splat(m[19]);
+
+    p.next_stage();
+}
+
+fn byte_tables(p: &mut Pipeline) {
+    if let Some(ref tables) = p.ctx.byte_tables {
+        p.r = byte_table_lookup(&tables[0], p.r);
+        p.g = byte_table_lookup(&tables[1], p.g);
+        p.b = byte_table_lookup(&tables[2], p.b);
+        p.a = byte_table_lookup(&tables[3], p.a);
+    }
+
+    p.next_stage();
+}
+
+fn lut_3d_trilinear(p: &mut Pipeline) {
+    if let Some(ref lut) = p.ctx.lut {
+        let (r, g, b) = lut_3d_lookup(p.r, p.g, p.b, |c| lut.sample_trilinear(c));
+        p.r = r;
+        p.g = g;
+        p.b = b;
+    }
+
+    p.next_stage();
+}
+
+fn lut_3d_tetrahedral(p: &mut Pipeline) {
+    if let Some(ref lut) = p.ctx.lut {
+        let (r, g, b) = lut_3d_lookup(p.r, p.g, p.b, |c| lut.sample_tetrahedral(c));
+        p.r = r;
+        p.g = g;
+        p.b = b;
+    }
+
+    p.next_stage();
+}
+
+#[inline(always)]
+fn lut_3d_lookup(
+    r: f32x8, g: f32x8, b: f32x8,
+    sample: impl Fn([f32; 3]) -> [f32; 3],
+) -> (f32x8, f32x8, f32x8) {
+    // No SIMD gather, so we have to process each value separately.
+    let mut r: [f32; 8] = r.into();
+    let mut g: [f32; 8] = g.into();
+    let mut b: [f32; 8] = b.into();
+    for i in 0..STAGE_WIDTH {
+        let c = sample([r[i], g[i], b[i]]);
+        r[i] = c[0];
+        g[i] = c[1];
+        b[i] = c[2];
+    }
+
+    (f32x8::from(r), f32x8::from(g), f32x8::from(b))
+}
+
+#[inline(always)]
+fn byte_table_lookup(table: &[u8; 256], v: f32x8) -> f32x8 {
+    // No SIMD gather, so we have to process each value separately.
+    let mut v: [f32; 8] = v.into();
+    for c in &mut v {
+        let idx = (c.bound(0.0, 1.0) * 255.0 + 0.5) as usize;
+        *c = table[idx] as f32 * (1.0 / 255.0);
+    }
+
+    f32x8::from(v)
+}
+
 fn move_source_to_destination(p: &mut Pipeline) {
     p.dr = p.r;
     p.dg = p.g;
@@ -195,6 +428,29 @@ fn premultiply(p: &mut Pipeline) {
     p.next_stage();
 }
 
+fn unpremultiply(p: &mut Pipeline) {
+    let scale = unpremultiply_scale(p.a);
+    p.r *= scale;
+    p.g *= scale;
+    p.b *= scale;
+
+    p.next_stage();
+}
+
+// Like `unpremultiply`, but for polar colors, where the hue is not premultiplied.
+fn unpremultiply_polar(p: &mut Pipeline) {
+    let scale = unpremultiply_scale(p.a);
+    p.r *= scale;
+    p.g *= scale;
+
+    p.next_stage();
+}
+
+#[inline(always)]
+fn unpremultiply_scale(a: f32x8) -> f32x8 {
+    a.cmp_gt(f32x8::default()).blend(f32x8::splat(1.0) / a, f32x8::default())
+}
+
 fn move_destination_to_source(p: &mut Pipeline) {
     p.r = p.dr;
     p.g = p.dg;
@@ -264,21 +520,25 @@ pub fn store(p: &mut Pipeline) {
 }
 
 pub fn gather(p: &mut Pipeline) {
-    let ix = gather_ix(p.pixmap_src, p.r, p.g);
+    let ix = gather_ix(p.pixmap_src, &p.ctx.sampler, p.r, p.g);
     load_8888(&p.pixmap_src.gather(ix), &mut p.r, &mut p.g, &mut p.b, &mut p.a);
 
     p.next_stage();
 }
 
 #[inline(always)]
-fn gather_ix(pixmap: PixmapRef, mut x: f32x8, mut y: f32x8) -> u32x8 {
+fn gather_ix(pixmap: PixmapRef, ctx: &super::SamplerCtx, mut x: f32x8, mut y: f32x8) -> u32x8 {
     // Exclusive -> inclusive.
-    let w = ulp_sub(pixmap.width() as f32);
-    let h = ulp_sub(pixmap.height() as f32);
-    x = x.max(f32x8::default()).min(f32x8::splat(w));
-    y = y.max(f32x8::default()).min(f32x8::splat(h));
+    let right = ulp_sub(ctx.subset_right);
+    let bottom = ulp_sub(ctx.subset_bottom);
+    x = x.max(f32x8::splat(ctx.subset_left)).min(f32x8::splat(right));
+    y = y.max(f32x8::splat(ctx.subset_top)).min(f32x8::splat(bottom));
 
-    (y.trunc_int() * i32x8::splat(pixmap.width() as i32) + x.trunc_int()).to_u32x8_bitcast()
+    // Rows can be padded or stored bottom-up, so we have to use the stride
+    // and the first row offset instead of the width.
+    let stride = i32x8::splat(pixmap.stride_in_pixels() as i32);
+    let origin = i32x8::splat(pixmap.offset(0, 0) as i32);
+    (y.trunc_int() * stride + x.trunc_int() + origin).to_u32x8_bitcast()
 }
 
 #[inline(always)]
@@ -350,6 +610,16 @@ fn scale_1_float(p: &mut Pipeline) {
     p.next_stage();
 }
 
+fn opacity(p: &mut Pipeline) {
+    let c = f32x8::splat(p.ctx.opacity);
+    p.r *= c;
+    p.g *= c;
+    p.b *= c;
+    p.a *= c;
+
+    p.next_stage();
+}
+
 fn lerp_1_float(p: &mut Pipeline) {
     let c = f32x8::splat(p.ctx.current_coverage);
     p.r = lerp(p.dr, p.r, c);
@@ -624,6 +894,23 @@ fn clip_color(r: &mut f32x8, g: &mut f32x8, b: &mut f32x8, a: f32x8) {
     *b = clip(*b);
 }
 
+fn arithmetic(p: &mut Pipeline) {
+    let ctx = p.ctx.arithmetic;
+    let k1 = f32x8::splat(ctx.k1);
+    let k2 = f32x8::splat(ctx.k2);
+    let k3 = f32x8::splat(ctx.k3);
+    let k4 = f32x8::splat(ctx.k4);
+    let f = |s: f32x8, d: f32x8| (k1 * s * d + k2 * s + k3 * d + k4).normalize();
+
+    p.a = f(p.a, p.da);
+    // Make sure that the result is a valid premultiplied color.
+    p.r = f(p.r, p.dr).min(p.a);
+    p.g = f(p.g, p.dg).min(p.a);
+    p.b = f(p.b, p.db).min(p.a);
+
+    p.next_stage();
+}
+
 pub fn source_over_rgba(p: &mut Pipeline) {
     let pixels = p.pixmap_dst.slice4_at_xy(p.dx, p.dy);
     load_8888(pixels, &mut p.dr, &mut p.dg, &mut p.db, &mut p.da);
@@ -663,10 +950,14 @@ fn transform(p: &mut Pipeline) {
 // The gather stages will hard clamp the output of these stages to [0,limit)...
 // we just need to do the basic repeat or mirroring.
 
-fn reflect(p: &mut Pipeline) {
+fn reflect_x(p: &mut Pipeline) {
     let ctx = &p.ctx.limit_x;
     p.r = exclusive_reflect(p.r, ctx.scale, ctx.inv_scale);
 
+    p.next_stage();
+}
+
+fn reflect_y(p: &mut Pipeline) {
     let ctx = &p.ctx.limit_y;
     p.g = exclusive_reflect(p.g, ctx.scale, ctx.inv_scale);
 
@@ -681,10 +972,14 @@ fn exclusive_reflect(v: f32x8, limit: f32, inv_limit: f32) -> f32x8 {
         * ((v - limit) * (inv_limit * f32x8::splat(0.5))).floor() - limit).abs()
 }
 
-fn repeat(p: &mut Pipeline) {
+fn repeat_x(p: &mut Pipeline) {
     let ctx = &p.ctx.limit_x;
     p.r = exclusive_repeat(p.r, ctx.scale, ctx.inv_scale);
 
+    p.next_stage();
+}
+
+fn repeat_y(p: &mut Pipeline) {
     let ctx = &p.ctx.limit_y;
     p.g = exclusive_repeat(p.g, ctx.scale, ctx.inv_scale);
 
@@ -724,6 +1019,92 @@ fn bicubic(p: &mut Pipeline) {
     p.next_stage();
 }
 
+// Like `bicubic`, but with custom B and C values.
+fn cubic(p: &mut Pipeline) {
+    let x = p.r;
+    let fx = (x + f32x8::splat(0.5)).fract();
+    let y = p.g;
+    let fy = (y + f32x8::splat(0.5)).fract();
+    let coeffs = &p.ctx.sampler.cubic_coeffs;
+    let wx = [
+        cubic_weight(&coeffs[0], fx), cubic_weight(&coeffs[1], fx),
+        cubic_weight(&coeffs[2], fx), cubic_weight(&coeffs[3], fx),
+    ];
+    let wy = [
+        cubic_weight(&coeffs[0], fy), cubic_weight(&coeffs[1], fy),
+        cubic_weight(&coeffs[2], fy), cubic_weight(&coeffs[3], fy),
+    ];
+
+    sampler_4x4(p.pixmap_src, &p.ctx.sampler, x, y, &wx, &wy, &mut p.r, &mut p.g, &mut p.b, &mut p.a);
+
+    p.next_stage();
+}
+
+// c0 + c1*t + c2*t^2 + c3*t^3 == t ( t ( c3*t + c2) + c1) + c0
+#[inline(always)]
+fn cubic_weight(c: &[f32; 4], t: f32x8) -> f32x8 {
+    mad(
+        t,
+        mad(t, mad(f32x8::splat(c[3]), t, f32x8::splat(c[2])), f32x8::splat(c[1])),
+        f32x8::splat(c[0]),
+    )
+}
+
+fn lanczos3(p: &mut Pipeline) {
+    let x = p.r;
+    let fx = (x + f32x8::splat(0.5)).fract();
+    let y = p.g;
+    let fy = (y + f32x8::splat(0.5)).fract();
+    let wx = lanczos3_weights(fx);
+    let wy = lanczos3_weights(fy);
+
+    sampler_6x6(p.pixmap_src, &p.ctx.sampler, x, y, &wx, &wy, &mut p.r, &mut p.g, &mut p.b, &mut p.a);
+
+    p.next_stage();
+}
+
+// Unlike cubic filters, Lanczos weights are not polynomial,
+// so we have to calculate them per lane.
+//
+// Weights are normalized, since the kernel doesn't sum up to 1 exactly.
+#[inline(always)]
+fn lanczos3_weights(t: f32x8) -> [f32x8; 6] {
+    let t: [f32; STAGE_WIDTH] = t.into();
+    let mut weights = [[0.0; STAGE_WIDTH]; 6];
+    for lane in 0..STAGE_WIDTH {
+        let mut sum = 0.0;
+        for (i, w) in weights.iter_mut().enumerate() {
+            // A distance from the sample point to the pixel center.
+            w[lane] = lanczos3_kernel(t[lane] + 2.0 - i as f32);
+            sum += w[lane];
+        }
+
+        for w in &mut weights {
+            w[lane] /= sum;
+        }
+    }
+
+    [
+        f32x8::from(weights[0]), f32x8::from(weights[1]), f32x8::from(weights[2]),
+        f32x8::from(weights[3]), f32x8::from(weights[4]), f32x8::from(weights[5]),
+    ]
+}
+
+// sinc(x) * sinc(x/3)
+#[inline(always)]
+fn lanczos3_kernel(x: f32) -> f32 {
+    if x.abs() >= 3.0 {
+        return 0.0;
+    }
+
+    let px = core::f32::consts::PI * x;
+    if px.abs() < f32::EPSILON {
+        return 1.0;
+    }
+
+    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+}
+
 // In bicubic interpolation, the 16 pixels and +/- 0.5 and +/- 1.5 offsets from the sample
 // pixel center are combined with a non-uniform cubic filter, with higher values near the center.
 //
@@ -828,22 +1209,61 @@ fn sampler_4x4(
     }
 }
 
+#[inline(always)]
+fn sampler_6x6(
+    pixmap: PixmapRef,
+    ctx: &super::SamplerCtx,
+    cx: f32x8, cy: f32x8,
+    wx: &[f32x8; 6], wy: &[f32x8; 6],
+    r: &mut f32x8, g: &mut f32x8, b: &mut f32x8, a: &mut f32x8,
+) {
+    *r = f32x8::default();
+    *g = f32x8::default();
+    *b = f32x8::default();
+    *a = f32x8::default();
+
+    let one = f32x8::splat(1.0);
+    let start = -2.5;
+    let mut y = cy + f32x8::splat(start);
+    for j in 0..6 {
+        let mut x = cx + f32x8::splat(start);
+        for i in 0..6 {
+            let mut rr = f32x8::default();
+            let mut gg = f32x8::default();
+            let mut bb = f32x8::default();
+            let mut aa = f32x8::default();
+            sample(pixmap, ctx, x, y, &mut rr, &mut gg, &mut bb, &mut aa);
+
+            let w = wx[i] * wy[j];
+            *r = mad(w, rr, *r);
+            *g = mad(w, gg, *g);
+            *b = mad(w, bb, *b);
+            *a = mad(w, aa, *a);
+
+            x += one;
+        }
+
+        y += one;
+    }
+}
+
 #[inline(always)]
 fn sample(
     pixmap: PixmapRef, ctx: &super::SamplerCtx, mut x: f32x8, mut y: f32x8,
     r: &mut f32x8, g: &mut f32x8, b: &mut f32x8, a: &mut f32x8,
 ) {
-    x = tile(x, ctx.spread_mode, pixmap.width() as f32, ctx.inv_width);
-    y = tile(y, ctx.spread_mode, pixmap.height() as f32, ctx.inv_height);
+    x = tile(x, ctx.spread_mode_x, pixmap.width() as f32, ctx.inv_width);
+    y = tile(y, ctx.spread_mode_y, pixmap.height() as f32, ctx.inv_height);
 
-    let ix = gather_ix(pixmap, x, y);
+    let ix = gather_ix(pixmap, ctx, x, y);
     load_8888(&pixmap.gather(ix), r, g, b, a);
 }
 
 #[inline(always)]
 fn tile(v: f32x8, mode: SpreadMode, limit: f32, inv_limit: f32) -> f32x8 {
     match mode {
-        SpreadMode::Pad => v,
+        // Decal is handled by the `decal_*` stages, so we can simply clamp.
+        SpreadMode::Pad | SpreadMode::Decal => v,
         SpreadMode::Repeat => exclusive_repeat(v, limit, inv_limit),
         SpreadMode::Reflect => exclusive_reflect(v, limit, inv_limit),
     }
@@ -935,6 +1355,35 @@ fn gradient_lookup(
     *a = mad(t, fa, ba);
 }
 
+fn gradient_lut(p: &mut Pipeline) {
+    let ctx = &p.ctx.gradient_lut;
+
+    let last = ctx.colors.len() - 1;
+    let t: [f32; 8] = (p.r.normalize() * f32x8::splat(last as f32)).into();
+    let mut r = [0.0; 8];
+    let mut g = [0.0; 8];
+    let mut b = [0.0; 8];
+    let mut a = [0.0; 8];
+    for i in 0..STAGE_WIDTH {
+        // `t` is in a 0..=last range, so `idx + 1` is always valid.
+        let idx = (t[i] as usize).min(last - 1);
+        let f = t[i] - idx as f32;
+        let c0 = &ctx.colors[idx];
+        let c1 = &ctx.colors[idx + 1];
+        r[i] = c0.r + (c1.r - c0.r) * f;
+        g[i] = c0.g + (c1.g - c0.g) * f;
+        b[i] = c0.b + (c1.b - c0.b) * f;
+        a[i] = c0.a + (c1.a - c0.a) * f;
+    }
+
+    p.r = f32x8::from(r);
+    p.g = f32x8::from(g);
+    p.b = f32x8::from(b);
+    p.a = f32x8::from(a);
+
+    p.next_stage();
+}
+
 fn evenly_spaced_2_stop_gradient(p: &mut Pipeline) {
     let ctx = &p.ctx.evenly_spaced_2_stop_gradient;
 
@@ -983,6 +1432,72 @@ fn xy_to_2pt_conical_greater(p: &mut Pipeline) {
     p.next_stage();
 }
 
+fn xy_to_2pt_conical_smaller(p: &mut Pipeline) {
+    let ctx = &p.ctx.two_point_conical_gradient;
+
+    let x = p.r;
+    let y = p.g;
+    p.r = -(x * x - y * y).sqrt() - x * f32x8::splat(ctx.p0);
+
+    p.next_stage();
+}
+
+fn xy_to_2pt_conical_strip(p: &mut Pipeline) {
+    let ctx = &p.ctx.two_point_conical_gradient;
+
+    let x = p.r;
+    let y = p.g;
+    p.r = x + (f32x8::splat(ctx.p0) - y * y).sqrt();
+
+    p.next_stage();
+}
+
+fn alter_2pt_conical_radial(p: &mut Pipeline) {
+    let ctx = &p.ctx.two_point_conical_gradient;
+
+    p.r = mad(p.r, f32x8::splat(ctx.p0), f32x8::splat(ctx.p1));
+
+    p.next_stage();
+}
+
+fn alter_2pt_conical_compensate_focal(p: &mut Pipeline) {
+    let ctx = &p.ctx.two_point_conical_gradient;
+
+    // Maps t from the focal space back to [0, 1], which is `t * (1 - f) + f`.
+    p.r = mad(p.r, f32x8::splat(1.0 - ctx.p1), f32x8::splat(ctx.p1));
+
+    p.next_stage();
+}
+
+fn alter_2pt_conical_unswap(p: &mut Pipeline) {
+    p.r = f32x8::splat(1.0) - p.r;
+
+    p.next_stage();
+}
+
+fn mask_2pt_conical_nan(p: &mut Pipeline) {
+    let ctx = &mut p.ctx.two_point_conical_gradient;
+
+    let t = p.r;
+    let is_degenerate = t.cmp_ne(t);
+    p.r = is_degenerate.blend(f32x8::default(), t);
+
+    let is_not_degenerate = !is_degenerate.to_u32x8_bitcast();
+    let is_not_degenerate: [u32; 8] = bytemuck::cast(is_not_degenerate);
+    ctx.mask = bytemuck::cast([
+        if is_not_degenerate[0] != 0 { !0 } else { 0 },
+        if is_not_degenerate[1] != 0 { !0 } else { 0 },
+        if is_not_degenerate[2] != 0 { !0 } else { 0 },
+        if is_not_degenerate[3] != 0 { !0 } else { 0 },
+        if is_not_degenerate[4] != 0 { !0 } else { 0 },
+        if is_not_degenerate[5] != 0 { !0 } else { 0 },
+        if is_not_degenerate[6] != 0 { !0 } else { 0 },
+        if is_not_degenerate[7] != 0 { !0 } else { 0 },
+    ]);
+
+    p.next_stage();
+}
+
 fn mask_2pt_conical_degenerates(p: &mut Pipeline) {
     let ctx = &mut p.ctx.two_point_conical_gradient;
 
@@ -1006,6 +1521,53 @@ fn mask_2pt_conical_degenerates(p: &mut Pipeline) {
     p.next_stage();
 }
 
+// Unlike `pad_x1`, doesn't clamp t, but remembers which lanes are outside
+// the [0,1] range, so `check_decal_mask` could make them transparent.
+fn decal_x1(p: &mut Pipeline) {
+    let mask = p.r.cmp_ge(f32x8::default()) & p.r.cmp_le(f32x8::splat(1.0));
+    p.ctx.decal.mask = mask.to_u32x8_bitcast();
+
+    p.next_stage();
+}
+
+// Like `decal_x1`, but for pixmaps, where the limit is exclusive.
+fn decal_x(p: &mut Pipeline) {
+    let mask = decal_mask(p.r, p.ctx.limit_x.scale);
+    p.ctx.decal.mask = mask.to_u32x8_bitcast();
+
+    p.next_stage();
+}
+
+fn decal_y(p: &mut Pipeline) {
+    let mask = decal_mask(p.g, p.ctx.limit_y.scale);
+    p.ctx.decal.mask = mask.to_u32x8_bitcast();
+
+    p.next_stage();
+}
+
+fn decal_x_and_y(p: &mut Pipeline) {
+    let mask = decal_mask(p.r, p.ctx.limit_x.scale) & decal_mask(p.g, p.ctx.limit_y.scale);
+    p.ctx.decal.mask = mask.to_u32x8_bitcast();
+
+    p.next_stage();
+}
+
+#[inline(always)]
+fn decal_mask(v: f32x8, limit: f32) -> f32x8 {
+    v.cmp_ge(f32x8::default()) & v.cmp_lt(f32x8::splat(limit))
+}
+
+fn check_decal_mask(p: &mut Pipeline) {
+    let ctx = &p.ctx.decal;
+
+    p.r = (p.r.to_u32x8_bitcast() & ctx.mask).to_f32x8_bitcast();
+    p.g = (p.g.to_u32x8_bitcast() & ctx.mask).to_f32x8_bitcast();
+    p.b = (p.b.to_u32x8_bitcast() & ctx.mask).to_f32x8_bitcast();
+    p.a = (p.a.to_u32x8_bitcast() & ctx.mask).to_f32x8_bitcast();
+
+    p.next_stage();
+}
+
 fn apply_vector_mask(p: &mut Pipeline) {
     let ctx = &p.ctx.two_point_conical_gradient;
 
@@ -1017,6 +1579,75 @@ fn apply_vector_mask(p: &mut Pipeline) {
     p.next_stage();
 }
 
+// Expects LCH in r, g, b. Hue is in degrees.
+fn oklch_to_oklab(p: &mut Pipeline) {
+    let chroma = p.g;
+    let hue: [f32; 8] = p.b.into();
+    let mut cos = [0.0; 8];
+    let mut sin = [0.0; 8];
+    for i in 0..STAGE_WIDTH {
+        let h = hue[i].to_radians();
+        cos[i] = h.cos();
+        sin[i] = h.sin();
+    }
+
+    p.g = chroma * f32x8::from(cos);
+    p.b = chroma * f32x8::from(sin);
+
+    p.next_stage();
+}
+
+// From https://bottosson.github.io/posts/oklab/
+fn oklab_to_linear_srgb(p: &mut Pipeline) {
+    let l = p.r;
+    let a = p.g;
+    let b = p.b;
+
+    let l_ = l + f32x8::splat(0.3963377774) * a + f32x8::splat(0.2158037573) * b;
+    let m_ = l - f32x8::splat(0.1055613458) * a - f32x8::splat(0.0638541728) * b;
+    let s_ = l - f32x8::splat(0.0894841775) * a - f32x8::splat(1.2914855480) * b;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    p.r = f32x8::splat( 4.0767416621) * l - f32x8::splat(3.3077115913) * m
+        + f32x8::splat(0.2309699292) * s;
+    p.g = f32x8::splat(-1.2684380046) * l + f32x8::splat(2.6097574011) * m
+        - f32x8::splat(0.3413193965) * s;
+    p.b = f32x8::splat(-0.0041960863) * l - f32x8::splat(0.7034186147) * m
+        + f32x8::splat(1.7076147010) * s;
+
+    p.next_stage();
+}
+
+fn linear_to_srgb(p: &mut Pipeline) {
+    p.r = linear_to_srgb_channel(p.r);
+    p.g = linear_to_srgb_channel(p.g);
+    p.b = linear_to_srgb_channel(p.b);
+
+    p.next_stage();
+}
+
+#[inline(always)]
+fn linear_to_srgb_channel(v: f32x8) -> f32x8 {
+    // No SIMD powf, so we have to process each value separately.
+    let mut v: [f32; 8] = v.into();
+    for c in &mut v {
+        // Keep the sign of out of gamut values, like CSS does.
+        let abs = c.abs();
+        let encoded = if abs <= 0.0031308 {
+            abs * 12.92
+        } else {
+            1.055 * abs.powf(1.0 / 2.4) - 0.055
+        };
+
+        *c = if *c < 0.0 { -encoded } else { encoded };
+    }
+
+    f32x8::from(v)
+}
+
 pub fn just_return(_: &mut Pipeline) {
     // Ends the loop.
 }
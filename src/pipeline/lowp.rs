@@ -35,13 +35,13 @@ pub const STAGE_WIDTH: usize = 16;
 
 pub type StageFn = fn(p: &mut Pipeline);
 
-pub struct Pipeline<'a, 'b: 'a> {
+pub struct Pipeline<'a, 'b: 'a, 'c: 'a> {
     index: usize,
     functions: &'a [StageFn],
     pixmap: &'a mut PixmapMut<'b>,
     clip_mask_ctx: super::ClipMaskCtx<'a>,
     mask_ctx: super::AAMaskCtx,
-    ctx: &'a mut super::Context,
+    ctx: &'a mut super::Context<'c>,
     r: u16x16,
     g: u16x16,
     b: u16x16,
@@ -55,7 +55,7 @@ pub struct Pipeline<'a, 'b: 'a> {
     dy: usize,
 }
 
-impl Pipeline<'_, '_> {
+impl Pipeline<'_, '_, '_> {
     #[inline(always)]
     fn next_stage(&mut self) {
         let next: fn(&mut Self) = self.functions[self.index];
@@ -72,6 +72,8 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     null_fn, // Clamp0
     null_fn, // ClampA
     premultiply,
+    null_fn, // Unpremultiply
+    null_fn, // UnpremultiplyPolar
     uniform_color,
     seed_shader,
     load_dst,
@@ -111,10 +113,14 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     null_fn, // Luminosity
     source_over_rgba,
     transform,
-    null_fn, // Reflect
-    null_fn, // Repeat
+    null_fn, // ReflectX
+    null_fn, // ReflectY
+    null_fn, // RepeatX
+    null_fn, // RepeatY
     null_fn, // Bilinear
     null_fn, // Bicubic
+    null_fn, // Cubic
+    null_fn, // Lanczos3
     pad_x1,
     reflect_x1,
     repeat_x1,
@@ -124,8 +130,33 @@ pub const STAGES: &[StageFn; super::STAGES_COUNT] = &[
     null_fn, // XYTo2PtConicalFocalOnCircle
     null_fn, // XYTo2PtConicalWellBehaved
     null_fn, // XYTo2PtConicalGreater
+    null_fn, // XYTo2PtConicalSmaller
+    null_fn, // XYTo2PtConicalStrip
+    null_fn, // Alter2PtConicalRadial
+    null_fn, // Alter2PtConicalCompensateFocal
+    null_fn, // Alter2PtConicalUnswap
+    null_fn, // Mask2PtConicalNan
     null_fn, // Mask2PtConicalDegenerates
     null_fn, // ApplyVectorMask
+    null_fn, // OklchToOklab
+    null_fn, // OklabToLinearSrgb
+    null_fn, // LinearToSrgb
+    gradient_lut,
+    null_fn, // ComposeShaders
+    null_fn, // LerpShaders
+    null_fn, // PerlinNoise
+    null_fn, // DecalX1
+    null_fn, // DecalX
+    null_fn, // DecalY
+    null_fn, // DecalXAndY
+    null_fn, // CheckDecalMask
+    null_fn, // Custom
+    null_fn, // ColorMatrix
+    null_fn, // ByteTables
+    null_fn, // Lut3DTrilinear
+    null_fn, // Lut3DTetrahedral
+    null_fn, // Arithmetic
+    opacity,
 ];
 
 pub fn fn_ptr(f: StageFn) -> *const () {
@@ -351,6 +382,16 @@ fn scale_1_float(p: &mut Pipeline) {
     p.next_stage();
 }
 
+fn opacity(p: &mut Pipeline) {
+    let c = from_float(p.ctx.opacity);
+    p.r = div255(p.r * c);
+    p.g = div255(p.g * c);
+    p.b = div255(p.b * c);
+    p.a = div255(p.a * c);
+
+    p.next_stage();
+}
+
 fn lerp_1_float(p: &mut Pipeline) {
     let c = from_float(p.ctx.current_coverage);
     p.r = lerp(p.dr, p.r, c);
@@ -531,6 +572,43 @@ fn gradient(p: &mut Pipeline) {
     p.next_stage();
 }
 
+fn gradient_lut(p: &mut Pipeline) {
+    let ctx = &p.ctx.gradient_lut;
+
+    let last = ctx.colors.len() - 1;
+    let t = join(&p.r, &p.g).normalize() * f32x16::splat(last as f32);
+    let t0: [f32; 8] = t.0[0].into();
+    let t1: [f32; 8] = t.0[1].into();
+    let mut r = [0.0; 16];
+    let mut g = [0.0; 16];
+    let mut b = [0.0; 16];
+    let mut a = [0.0; 16];
+    for i in 0..STAGE_WIDTH {
+        let t = if i < 8 { t0[i] } else { t1[i - 8] };
+        // `t` is in a 0..=last range, so `idx + 1` is always valid.
+        let idx = (t as usize).min(last - 1);
+        let f = t - idx as f32;
+        let c0 = &ctx.colors[idx];
+        let c1 = &ctx.colors[idx + 1];
+        r[i] = c0.r + (c1.r - c0.r) * f;
+        g[i] = c0.g + (c1.g - c0.g) * f;
+        b[i] = c0.b + (c1.b - c0.b) * f;
+        a[i] = c0.a + (c1.a - c0.a) * f;
+    }
+
+    let to_f32x16 = |v: [f32; 16]| f32x16([
+        f32x8::from(*arrayref::array_ref!(v, 0, 8)),
+        f32x8::from(*arrayref::array_ref!(v, 8, 8)),
+    ]);
+
+    round_f32_to_u16(
+        to_f32x16(r), to_f32x16(g), to_f32x16(b), to_f32x16(a),
+        &mut p.r, &mut p.g, &mut p.b, &mut p.a,
+    );
+
+    p.next_stage();
+}
+
 fn evenly_spaced_2_stop_gradient(p: &mut Pipeline) {
     let ctx = &p.ctx.evenly_spaced_2_stop_gradient;
 
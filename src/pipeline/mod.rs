@@ -44,12 +44,16 @@ There are still some exceptions, which are basically an imperfect implementation
 and should be optimized out in the future.
 */
 
+use alloc::boxed::Box;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 use arrayvec::ArrayVec;
 
 use crate::{LengthU32, Color, SpreadMode, PremultipliedColor, PremultipliedColorU8};
-use crate::{Transform, PixmapRef, PixmapMut};
+use crate::{Transform, Pixmap, PixmapRef, PixmapMut};
+use crate::ColorLut3D;
+use crate::shaders::CustomShaderFn;
 
 pub use blitter::RasterPipelineBlitter;
 
@@ -72,6 +76,8 @@ pub enum Stage {
     Clamp0,
     ClampA,
     Premultiply,
+    Unpremultiply,
+    UnpremultiplyPolar,
     UniformColor,
     SeedShader,
     LoadDestination,
@@ -111,10 +117,14 @@ pub enum Stage {
     Luminosity,
     SourceOverRgba,
     Transform,
-    Reflect,
-    Repeat,
+    ReflectX,
+    ReflectY,
+    RepeatX,
+    RepeatY,
     Bilinear,
     Bicubic,
+    Cubic,
+    Lanczos3,
     PadX1,
     ReflectX1,
     RepeatX1,
@@ -124,11 +134,38 @@ pub enum Stage {
     XYTo2PtConicalFocalOnCircle,
     XYTo2PtConicalWellBehaved,
     XYTo2PtConicalGreater,
+    XYTo2PtConicalSmaller,
+    XYTo2PtConicalStrip,
+    Alter2PtConicalRadial,
+    Alter2PtConicalCompensateFocal,
+    Alter2PtConicalUnswap,
+    Mask2PtConicalNan,
     Mask2PtConicalDegenerates,
     ApplyVectorMask,
+    OklchToOklab,
+    OklabToLinearSrgb,
+    LinearToSrgb,
+    GradientLut,
+    ComposeShaders,
+    LerpShaders,
+    PerlinNoise,
+    DecalX1,
+    DecalX,
+    DecalY,
+    DecalXAndY,
+    CheckDecalMask,
+    Custom,
+    ColorMatrix,
+    ByteTables,
+    Lut3DTrilinear,
+    Lut3DTetrahedral,
+    Arithmetic,
+    Opacity,
 }
 
-pub const STAGES_COUNT: usize = Stage::ApplyVectorMask as usize + 1;
+pub const STAGES_COUNT: usize = Stage::Opacity as usize + 1;
+
+pub const HIGHP_STAGE_WIDTH: usize = highp::STAGE_WIDTH;
 
 
 impl<'a> PixmapRef<'a> {
@@ -152,7 +189,7 @@ impl<'a> PixmapRef<'a> {
 impl<'a> PixmapMut<'a> {
     #[inline(always)]
     pub(crate) fn offset(&self, dx: usize, dy: usize) -> usize {
-        self.width() as usize * dy + dx
+        self.as_ref().offset(dx, dy)
     }
 
     #[inline(always)]
@@ -181,7 +218,7 @@ impl<'a> PixmapMut<'a> {
 }
 
 
-#[derive(Default, Debug)]
+#[derive(Copy, Clone, Default, Debug)]
 pub struct AAMaskCtx {
     pub pixels: [u8; 2],
     pub stride: u32, // can be zero
@@ -207,6 +244,9 @@ impl AAMaskCtx {
 pub struct ClipMaskCtx<'a> {
     pub data: &'a [u8],
     pub stride: LengthU32,
+    // Mask's storage offset.
+    pub x: u32,
+    pub y: u32,
 }
 
 impl Default for ClipMaskCtx<'_> {
@@ -214,6 +254,8 @@ impl Default for ClipMaskCtx<'_> {
         ClipMaskCtx {
             data: &[],
             stride: LENGTH_U32_ONE,
+            x: 0,
+            y: 0,
         }
     }
 }
@@ -221,30 +263,49 @@ impl Default for ClipMaskCtx<'_> {
 impl ClipMaskCtx<'_> {
     #[inline(always)]
     fn offset(&self, dx: usize, dy: usize) -> usize {
-        self.stride.get() as usize * dy + dx
+        self.stride.get() as usize * (dy - self.y as usize) + (dx - self.x as usize)
     }
 }
 
 
 #[derive(Default)]
-pub struct Context {
+pub struct Context<'a> {
     pub current_coverage: f32,
+    pub opacity: f32,
     pub sampler: SamplerCtx,
     pub uniform_color: UniformColorCtx,
     pub evenly_spaced_2_stop_gradient: EvenlySpaced2StopGradientCtx,
     pub gradient: GradientCtx,
+    pub gradient_lut: GradientLutCtx,
     pub two_point_conical_gradient: TwoPointConicalGradientCtx,
+    pub perlin_noise: PerlinNoiseCtx,
+    pub decal: DecalCtx,
+    pub custom: CustomCtx<'a>,
+    pub color_matrix: [f32; 20],
+    pub byte_tables: Option<Box<[[u8; 256]; 4]>>,
+    pub lut: Option<Arc<ColorLut3D>>,
+    pub arithmetic: ArithmeticCtx,
     pub limit_x: TileCtx,
     pub limit_y: TileCtx,
     pub transform: Transform,
+    pub compose: ComposeCtx<'a>,
 }
 
 
 #[derive(Copy, Clone, Default, Debug)]
 pub struct SamplerCtx {
-    pub spread_mode: SpreadMode,
+    pub spread_mode_x: SpreadMode,
+    pub spread_mode_y: SpreadMode,
     pub inv_width: f32,
     pub inv_height: f32,
+    // Pixels outside of this rect will never be sampled.
+    // Usually, the whole pixmap.
+    pub subset_left: f32,
+    pub subset_top: f32,
+    pub subset_right: f32,
+    pub subset_bottom: f32,
+    // Polynomial coefficients for each of the 4 taps. Used by `Cubic`.
+    pub cubic_coeffs: [[f32; 4]; 4],
 }
 
 
@@ -313,11 +374,130 @@ impl GradientCtx {
 }
 
 
+#[derive(Clone, Default, Debug)]
+pub struct GradientLutCtx {
+    /// Colors evenly sampled in a 0..=1 range.
+    /// Always has at least 2 values.
+    ///
+    /// Shared, since the table is built once per gradient and not per drawing operation.
+    pub colors: Arc<Vec<GradientColor>>,
+}
+
+impl GradientLutCtx {
+    pub fn new(gradient: &GradientCtx, size: usize) -> Self {
+        debug_assert!(size > 1);
+
+        let last = (size - 1) as f32;
+        let colors = (0..size).map(|i| {
+            let t = i as f32 / last;
+
+            // Must be the same as in the `Gradient` stage.
+            let idx = (1..gradient.len).filter(|n| t >= gradient.t_values[*n].get()).count();
+            let f = gradient.factors[idx];
+            let b = gradient.biases[idx];
+            GradientColor::new(
+                t * f.r + b.r,
+                t * f.g + b.g,
+                t * f.b + b.b,
+                t * f.a + b.a,
+            )
+        }).collect();
+
+        GradientLutCtx { colors: Arc::new(colors) }
+    }
+}
+
+
 #[derive(Copy, Clone, Default, Debug)]
 pub struct TwoPointConicalGradientCtx {
     // This context is used only in highp, where we use Tx4.
     pub mask: u32x8,
     pub p0: f32,
+    pub p1: f32,
+}
+
+
+#[derive(Clone, Default, Debug)]
+pub struct PerlinNoiseCtx {
+    pub fractal_noise: bool,
+    pub base_frequency_x: f32,
+    pub base_frequency_y: f32,
+    pub num_octaves: u32,
+    pub stitch: Option<StitchData>,
+    /// Has 2 * 256 + 2 values.
+    pub lattice_selector: Vec<u8>,
+    /// Has 4 * 256 values. One block per color channel.
+    pub gradients: Vec<[f32; 2]>,
+    pub opacity: f32,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct ArithmeticCtx {
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+    pub k4: f32,
+}
+
+#[derive(Clone, Default)]
+pub struct CustomCtx<'a> {
+    pub function: Option<Arc<dyn CustomShaderFn + 'a>>,
+    pub opacity: f32,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct StitchData {
+    pub width: i32,
+    pub height: i32,
+    pub wrap_x: i32,
+    pub wrap_y: i32,
+}
+
+
+/// A shader compiled into a separate pipeline.
+///
+/// Since all stages share a single context, we cannot push stages of two shaders
+/// into the same pipeline. Instead, each one will be evaluated independently
+/// and the results will be passed to the parent pipeline.
+pub struct ShaderPipeline<'a> {
+    pub functions: ArrayVec<[highp::StageFn; MAX_STAGES]>,
+    pub ctx: Context<'a>,
+    pub pixmap_src: ShaderPixmap<'a>,
+}
+
+
+pub enum ShaderPixmap<'a> {
+    Borrowed(PixmapRef<'a>),
+    /// Used by pixmaps generated by shaders, like mipmaps.
+    Shared(Arc<Pixmap>),
+}
+
+impl ShaderPixmap<'_> {
+    #[inline(always)]
+    pub fn as_ref(&self) -> PixmapRef<'_> {
+        match self {
+            ShaderPixmap::Borrowed(pixmap) => *pixmap,
+            ShaderPixmap::Shared(pixmap) => Pixmap::as_ref(pixmap),
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct ComposeCtx<'a> {
+    /// Will be stored in the source registers.
+    pub src: Option<Box<ShaderPipeline<'a>>>,
+    /// Will be stored in the destination registers.
+    pub dst: Option<Box<ShaderPipeline<'a>>>,
+    /// Used by `LerpShaders`.
+    pub t: f32,
+}
+
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DecalCtx {
+    // This context is used only in highp, where we use Tx4.
+    pub mask: u32x8,
 }
 
 
@@ -327,13 +507,13 @@ pub struct TileCtx {
     pub inv_scale: f32, // cache of 1/scale
 }
 
-pub struct RasterPipelineBuilder {
+pub struct RasterPipelineBuilder<'a> {
     stages: ArrayVec<[Stage; MAX_STAGES]>,
     force_hq_pipeline: bool,
-    pub ctx: Context,
+    pub ctx: Context<'a>,
 }
 
-impl RasterPipelineBuilder {
+impl<'a> RasterPipelineBuilder<'a> {
     pub fn new() -> Self {
         RasterPipelineBuilder {
             stages: ArrayVec::new(),
@@ -378,7 +558,23 @@ impl RasterPipelineBuilder {
         self.ctx.uniform_color = ctx;
     }
 
-    pub fn compile(self) -> RasterPipeline {
+    /// Compiles stages into a pipeline that can be evaluated by the `ComposeShaders` stage.
+    ///
+    /// Only highp is supported.
+    pub fn compile_shader(self, pixmap_src: ShaderPixmap<'a>) -> ShaderPipeline<'a> {
+        let mut functions: ArrayVec<_> = self.stages.iter()
+            .map(|stage| highp::STAGES[*stage as usize] as highp::StageFn)
+            .collect();
+        functions.push(highp::just_return as highp::StageFn);
+
+        ShaderPipeline {
+            functions,
+            ctx: self.ctx,
+            pixmap_src,
+        }
+    }
+
+    pub fn compile(self) -> RasterPipeline<'a> {
         if self.stages.is_empty() {
             return RasterPipeline {
                 kind: RasterPipelineKind::High {
@@ -459,12 +655,12 @@ pub enum RasterPipelineKind {
     },
 }
 
-pub struct RasterPipeline {
+pub struct RasterPipeline<'a> {
     kind: RasterPipelineKind,
-    pub ctx: Context,
+    pub ctx: Context<'a>,
 }
 
-impl RasterPipeline {
+impl RasterPipeline<'_> {
     pub fn run(
         &mut self,
         rect: &ScreenIntRect,
@@ -514,7 +710,7 @@ mod blend_tests {
     // due rounding.
 
     use super::*;
-    use crate::{Pixmap, Color, PremultipliedColorU8, BlendMode};
+    use crate::{Pixmap, Color, PremultipliedColorU8, BlendMode, Blender};
 
     macro_rules! test_blend {
         ($name:ident, $mode:expr, $is_highp:expr, $r:expr, $g:expr, $b:expr, $a:expr) => {
@@ -529,7 +725,7 @@ mod blend_tests {
                 p.set_force_hq_pipeline($is_highp);
                 p.push_uniform_color(Color::from_rgba8(220, 140, 75, 180).premultiply());
                 p.push(Stage::LoadDestination);
-                p.push($mode.to_stage().unwrap());
+                $mode.push_stages(&mut p);
                 p.push(Stage::Store);
                 let mut p = p.compile();
                 let rect = pixmap.size().to_screen_int_rect(0, 0);
@@ -615,4 +811,8 @@ mod blend_tests {
     test_blend_highp!(saturation_highp,       BlendMode::Saturation,           59, 126, 140, 239);
     test_blend_highp!(color_highp,            BlendMode::Color,               139, 100,  60, 239);
     test_blend_highp!(luminosity_highp,       BlendMode::Luminosity,          100, 149, 157, 239);
+    test_blend_highp!(arithmetic_highp,
+        Blender::Arithmetic { k1: 0.5, k2: 0.5, k3: 0.5, k4: 0.0 },              109, 119,  98, 255);
+    test_blend_highp!(arithmetic_clamped_highp,
+        Blender::Arithmetic { k1: 0.0, k2: 1.0, k3: 0.0, k4: 0.5 },              255, 226, 180, 255);
 }
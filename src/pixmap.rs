@@ -12,11 +12,14 @@ use alloc::vec::Vec;
 use core::convert::TryFrom;
 use core::num::NonZeroUsize;
 
-use crate::{Color, IntRect};
+use crate::{BlendMode, Color, FilterQuality, IntRect, PixmapPaint, Transform};
 
 use crate::color::PremultipliedColorU8;
 use crate::geom::{IntSize, ScreenIntRect};
 
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
 #[cfg(feature = "png-format")]
 use crate::color::{premultiply_u8, ALPHA_U8_OPAQUE};
 
@@ -181,6 +184,7 @@ impl Pixmap {
         PixmapRef {
             data: &self.data,
             size: self.size,
+            stride: self.size.width() as isize,
         }
     }
 
@@ -189,6 +193,7 @@ impl Pixmap {
         PixmapMut {
             data: &mut self.data,
             size: self.size,
+            stride: self.size.width() as isize,
         }
     }
 
@@ -263,6 +268,56 @@ impl Pixmap {
     pub fn clone_rect(&self, rect: IntRect) -> Option<Pixmap> {
         self.as_ref().clone_rect(rect)
     }
+
+    /// Returns a resized copy of the pixmap.
+    ///
+    /// See `PixmapRef::resize` for details.
+    pub fn resize(&self, width: u32, height: u32, quality: FilterQuality) -> Option<Pixmap> {
+        self.as_ref().resize(width, height, quality)
+    }
+
+    /// Returns a horizontally flipped copy of the pixmap.
+    pub fn flip_horizontal(&self) -> Pixmap {
+        self.as_ref().flip_horizontal()
+    }
+
+    /// Returns a vertically flipped copy of the pixmap.
+    pub fn flip_vertical(&self) -> Pixmap {
+        self.as_ref().flip_vertical()
+    }
+
+    /// Returns a copy of the pixmap rotated by 90 degrees clockwise.
+    ///
+    /// Returns `None` when the rotated size is invalid.
+    pub fn rotate_90(&self) -> Option<Pixmap> {
+        self.as_ref().rotate_90()
+    }
+
+    /// Returns a copy of the pixmap rotated by 180 degrees.
+    pub fn rotate_180(&self) -> Pixmap {
+        self.as_ref().rotate_180()
+    }
+
+    /// Returns a copy of the pixmap rotated by 270 degrees clockwise.
+    ///
+    /// Returns `None` when the rotated size is invalid.
+    pub fn rotate_270(&self) -> Option<Pixmap> {
+        self.as_ref().rotate_270()
+    }
+
+    /// Returns a copy of the pixmap's area specified by `rect`.
+    ///
+    /// See `PixmapRef::crop` for details.
+    pub fn crop(&self, rect: IntRect, fill: Color) -> Option<Pixmap> {
+        self.as_ref().crop(rect, fill)
+    }
+
+    /// Returns a copy of the pixmap with borders added on each side.
+    ///
+    /// See `PixmapRef::extend` for details.
+    pub fn extend(&self, left: u32, top: u32, right: u32, bottom: u32, fill: Color) -> Option<Pixmap> {
+        self.as_ref().extend(left, top, right, bottom, fill)
+    }
 }
 
 impl core::fmt::Debug for Pixmap {
@@ -276,15 +331,93 @@ impl core::fmt::Debug for Pixmap {
 }
 
 
+// A source pixels range and their weights.
+struct BoxTap {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+// Calculates which source pixels each destination pixel covers.
+// `dst` must not be bigger than `src`.
+fn box_taps(src: u32, dst: u32) -> Vec<BoxTap> {
+    let scale = src as f32 / dst as f32;
+    let mut taps = Vec::with_capacity(dst as usize);
+    for i in 0..dst {
+        let l = i as f32 * scale;
+        let r = ((i + 1) as f32 * scale).min(src as f32);
+        let start = l.floor() as u32;
+        let end = (r.ceil() as u32).min(src).max(start + 1);
+
+        let mut weights = Vec::with_capacity((end - start) as usize);
+        for j in start..end {
+            let covered = r.min((j + 1) as f32) - l.max(j as f32);
+            weights.push(covered.max(0.0) / scale);
+        }
+
+        taps.push(BoxTap { start: start as usize, weights });
+    }
+
+    taps
+}
+
+// Area-averaging downscale of premultiplied pixels.
+fn box_downscale(src: PixmapRef, width: u32, height: u32) -> Option<Pixmap> {
+    let mut new = Pixmap::new(width, height)?;
+
+    let x_taps = box_taps(src.width(), width);
+    let y_taps = box_taps(src.height(), height);
+
+    // Horizontal pass.
+    let mut tmp = vec![[0.0f32; 4]; width as usize * src.height() as usize];
+    for (src_row, tmp_row) in src.rows().zip(tmp.chunks_mut(width as usize)) {
+        for (tap, p) in x_taps.iter().zip(tmp_row.iter_mut()) {
+            for (c, w) in src_row[tap.start..].iter().zip(&tap.weights) {
+                p[0] += c.red() as f32 * w;
+                p[1] += c.green() as f32 * w;
+                p[2] += c.blue() as f32 * w;
+                p[3] += c.alpha() as f32 * w;
+            }
+        }
+    }
+
+    // Vertical pass.
+    let width = width as usize;
+    let new_pixels = new.pixels_mut();
+    for (y, tap) in y_taps.iter().enumerate() {
+        for x in 0..width {
+            let mut p = [0.0f32; 4];
+            for (i, w) in tap.weights.iter().enumerate() {
+                let c = tmp[(tap.start + i) * width + x];
+                for (v, c) in p.iter_mut().zip(&c) {
+                    *v += c * w;
+                }
+            }
+
+            let to_u8 = |v: f32| (v + 0.5).min(255.0) as u8;
+            let a = to_u8(p[3]);
+            new_pixels[y * width + x] = PremultipliedColorU8::from_rgba_unchecked(
+                to_u8(p[0]).min(a),
+                to_u8(p[1]).min(a),
+                to_u8(p[2]).min(a),
+                a,
+            );
+        }
+    }
+
+    Some(new)
+}
+
+
 /// A container that references premultiplied RGBA pixels.
 ///
 /// Can be created from `Pixmap` or from a user provided data.
 ///
-/// The data is not aligned, therefore width == stride.
-#[derive(Clone, Copy, PartialEq)]
+/// Rows can be padded or stored bottom-up. See `from_bytes_with_stride`.
+#[derive(Clone, Copy)]
 pub struct PixmapRef<'a> {
     data: &'a [u8],
     size: IntSize,
+    stride: isize, // in pixels, negative when rows are stored bottom-up
 }
 
 impl<'a> PixmapRef<'a> {
@@ -304,15 +437,47 @@ impl<'a> PixmapRef<'a> {
         Some(PixmapRef {
             data,
             size,
+            stride: width as isize,
+        })
+    }
+
+    /// Creates a new `PixmapRef` from bytes with an explicit row stride.
+    ///
+    /// `stride` is the distance between rows in bytes. It must be a multiple of
+    /// `BYTES_PER_PIXEL` and its absolute value must be at least `width * BYTES_PER_PIXEL`.
+    /// A negative stride indicates bottom-up rows, i.e. the first row in `data`
+    /// is the bottom row of the pixmap.
+    ///
+    /// The size must be at least `(height - 1) * abs(stride) + width * BYTES_PER_PIXEL`.
+    /// Zero size in an error. Width is limited by i32::MAX/4.
+    ///
+    /// The `data` is assumed to have premultiplied RGBA pixels (byteorder: RGBA).
+    pub fn from_bytes_with_stride(
+        data: &'a [u8],
+        width: u32,
+        height: u32,
+        stride: isize,
+    ) -> Option<Self> {
+        let size = IntSize::from_wh(width, height)?;
+        let stride = stride_in_pixels(data.len(), size, stride)?;
+        Some(PixmapRef {
+            data,
+            size,
+            stride,
         })
     }
 
     /// Creates a new `Pixmap` from the current data.
     ///
-    /// Clones the underlying data.
+    /// Clones the underlying data. Padding is not preserved and rows are stored top-down.
     pub fn to_owned(&self) -> Pixmap {
+        let mut data = Vec::with_capacity(self.width() as usize * self.height() as usize * BYTES_PER_PIXEL);
+        for row in self.rows() {
+            data.extend_from_slice(bytemuck::cast_slice(row));
+        }
+
         Pixmap {
-            data: self.data.to_vec(),
+            data,
             size: self.size,
         }
     }
@@ -339,9 +504,32 @@ impl<'a> PixmapRef<'a> {
         self.size.to_screen_int_rect(0, 0)
     }
 
+    /// Returns the distance between rows in bytes.
+    ///
+    /// Negative when rows are stored bottom-up.
+    pub fn stride(&self) -> isize {
+        self.stride * BYTES_PER_PIXEL as isize
+    }
+
+    /// Returns the distance between rows in pixels.
+    #[inline(always)]
+    pub(crate) fn stride_in_pixels(&self) -> isize {
+        self.stride
+    }
+
+    /// Returns an index of a pixel in `pixels()`.
+    ///
+    /// Position must be in bounds.
+    #[inline(always)]
+    pub(crate) fn offset(&self, x: usize, y: usize) -> usize {
+        row_offset(self.size, self.stride, y) + x
+    }
+
     /// Returns the internal data.
     ///
     /// Byteorder: RGBA
+    ///
+    /// Includes row padding, when present. Use `stride` to locate rows.
     pub fn data(&self) -> &'a [u8] {
         self.data
     }
@@ -350,16 +538,53 @@ impl<'a> PixmapRef<'a> {
     ///
     /// Returns `None` when position is out of bounds.
     pub fn pixel(&self, x: u32, y: u32) -> Option<PremultipliedColorU8> {
-        let idx = self.width().checked_mul(y)?.checked_add(x)?;
-        self.pixels().get(idx as usize).cloned()
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+
+        self.pixels().get(self.offset(x as usize, y as usize)).cloned()
     }
 
     /// Returns a slice of pixels.
+    ///
+    /// Includes row padding, when present. Use `row` or `rows` to access pixels by rows.
     pub fn pixels(&self) -> &'a [PremultipliedColorU8] {
         bytemuck::cast_slice(self.data())
     }
 
-    // TODO: add rows() iterator
+    /// Returns a row of pixels.
+    ///
+    /// Returns `None` when `y` is out of bounds.
+    pub fn row(&self, y: u32) -> Option<&'a [PremultipliedColorU8]> {
+        if y >= self.height() {
+            return None;
+        }
+
+        let start = self.offset(0, y as usize);
+        self.pixels().get(start..start + self.width() as usize)
+    }
+
+    /// Returns an iterator over pixmap's rows, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [PremultipliedColorU8]> + 'a {
+        let pixmap = *self;
+        // Rows are always in bounds.
+        (0..self.height()).map(move |y| pixmap.row(y).unwrap())
+    }
+
+    /// Returns a pixmap that references a part of the current one.
+    ///
+    /// Doesn't copy pixels. `rect` will be clipped by the pixmap's bounds.
+    ///
+    /// Returns `None` when `rect` is outside the pixmap.
+    pub fn sub_pixmap(&self, rect: IntRect) -> Option<PixmapRef<'a>> {
+        let rect = self.rect().to_int_rect().intersect(&rect)?.to_screen_int_rect()?;
+        let range = sub_pixmap_range(self.size, self.stride, rect)?;
+        Some(PixmapRef {
+            data: self.data.get(range)?,
+            size: rect.size(),
+            stride: self.stride,
+        })
+    }
 
     /// Returns a copy of the pixmap that intersects the `rect`.
     ///
@@ -367,26 +592,153 @@ impl<'a> PixmapRef<'a> {
     pub fn clone_rect(&self, rect: IntRect) -> Option<Pixmap> {
         // TODO: to ScreenIntRect?
 
-        let rect = self.rect().to_int_rect().intersect(&rect)?;
+        self.sub_pixmap(rect).map(|sub| sub.to_owned())
+    }
+
+    /// Returns a resized copy of the pixmap.
+    ///
+    /// Unless `quality` is `Nearest`, each downscaled axis is area-averaged first,
+    /// so all source pixels contribute to the result. Upscaling uses the `quality` filter.
+    ///
+    /// Returns `None` when the new size is invalid.
+    pub fn resize(&self, width: u32, height: u32, quality: FilterQuality) -> Option<Pixmap> {
+        let size = IntSize::from_wh(width, height)?;
+        if size == self.size {
+            return Some(self.to_owned());
+        }
+
+        let averaged;
+        let mut src = *self;
+        if quality != FilterQuality::Nearest && (width < self.width() || height < self.height()) {
+            averaged = box_downscale(
+                *self,
+                width.min(self.width()),
+                height.min(self.height()),
+            )?;
+            src = averaged.as_ref();
+        }
+
+        if src.size() == size {
+            return Some(src.to_owned());
+        }
+
+        let mut new = Pixmap::new(width, height)?;
+        let paint = PixmapPaint {
+            blend_mode: BlendMode::Source,
+            quality,
+            ..PixmapPaint::default()
+        };
+        let ts = Transform::from_scale(
+            width as f32 / src.width() as f32,
+            height as f32 / src.height() as f32,
+        );
+        new.as_mut().draw_pixmap(0, 0, src, &paint, ts, None)?;
+        Some(new)
+    }
+
+    /// Returns a horizontally flipped copy of the pixmap.
+    pub fn flip_horizontal(&self) -> Pixmap {
+        let mut new = self.to_owned();
+        let width = self.width() as usize;
+        for row in new.pixels_mut().chunks_mut(width) {
+            row.reverse();
+        }
+
+        new
+    }
+
+    /// Returns a vertically flipped copy of the pixmap.
+    pub fn flip_vertical(&self) -> Pixmap {
+        let mut data = Vec::with_capacity(self.width() as usize * self.height() as usize * BYTES_PER_PIXEL);
+        for y in (0..self.height()).rev() {
+            // Rows are always in bounds.
+            data.extend_from_slice(bytemuck::cast_slice(self.row(y).unwrap()));
+        }
+
+        Pixmap {
+            data,
+            size: self.size,
+        }
+    }
+
+    /// Returns a copy of the pixmap rotated by 90 degrees clockwise.
+    ///
+    /// Returns `None` when the rotated size is invalid.
+    pub fn rotate_90(&self) -> Option<Pixmap> {
+        let h = self.height();
+        self.transpose(|x, y| (h - 1 - y, x))
+    }
+
+    /// Returns a copy of the pixmap rotated by 180 degrees.
+    pub fn rotate_180(&self) -> Pixmap {
+        let mut new = self.to_owned();
+        new.pixels_mut().reverse();
+        new
+    }
+
+    /// Returns a copy of the pixmap rotated by 270 degrees clockwise.
+    ///
+    /// Returns `None` when the rotated size is invalid.
+    pub fn rotate_270(&self) -> Option<Pixmap> {
+        let w = self.width();
+        self.transpose(|x, y| (y, w - 1 - x))
+    }
+
+    // Copies pixels into a pixmap with swapped width and height.
+    // `f` maps a source position into a destination one.
+    fn transpose<F: Fn(u32, u32) -> (u32, u32)>(&self, f: F) -> Option<Pixmap> {
+        let (w, h) = (self.width(), self.height());
+        let mut new = Pixmap::new(h, w)?;
+        let new_pixels = new.pixels_mut();
+        for (y, row) in self.rows().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                let (nx, ny) = f(x as u32, y as u32);
+                new_pixels[(ny * h + nx) as usize] = *c;
+            }
+        }
+
+        Some(new)
+    }
+
+    /// Returns a copy of the pixmap's area specified by `rect`.
+    ///
+    /// Unlike `clone_rect`, `rect` can be partially or even fully outside the pixmap.
+    /// Such areas will be filled with the `fill` color.
+    ///
+    /// Returns `None` when `rect` size is invalid.
+    pub fn crop(&self, rect: IntRect, fill: Color) -> Option<Pixmap> {
         let mut new = Pixmap::new(rect.width(), rect.height())?;
-        {
-            let old_pixels = self.pixels();
-            let mut new_mut = new.as_mut();
-            let new_pixels = new_mut.pixels_mut();
-
-            // TODO: optimize
-            for y in 0..rect.height() {
-                for x in 0..rect.width() {
-                    let old_idx = (y + rect.y() as u32) * self.width() + (x + rect.x() as u32);
-                    let new_idx = y * rect.width() + x;
-                    new_pixels[new_idx as usize] = old_pixels[old_idx as usize];
-                }
+        new.fill(fill);
+
+        if let Some(src_rect) = self.rect().to_int_rect().intersect(&rect) {
+            let dx = (src_rect.x() - rect.x()) as usize;
+            let dy = (src_rect.y() - rect.y()) as usize;
+            let new_width = rect.width() as usize;
+            let len = src_rect.width() as usize;
+            let new_pixels = new.pixels_mut();
+            let src = self.sub_pixmap(src_rect)?;
+            for (y, row) in src.rows().enumerate() {
+                let new_idx = (dy + y) * new_width + dx;
+                new_pixels[new_idx..new_idx + len].copy_from_slice(row);
             }
         }
 
         Some(new)
     }
 
+    /// Returns a copy of the pixmap with borders added on each side.
+    ///
+    /// Borders are filled with the `fill` color.
+    ///
+    /// Returns `None` when the new size is invalid.
+    pub fn extend(&self, left: u32, top: u32, right: u32, bottom: u32, fill: Color) -> Option<Pixmap> {
+        let width = self.width().checked_add(left)?.checked_add(right)?;
+        let height = self.height().checked_add(top)?.checked_add(bottom)?;
+        let x = i32::try_from(left).ok()?.checked_neg()?;
+        let y = i32::try_from(top).ok()?.checked_neg()?;
+        self.crop(IntRect::from_xywh(x, y, width, height)?, fill)
+    }
+
     /// Encodes pixmap into a PNG data.
     #[cfg(feature = "png-format")]
     pub fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
@@ -443,11 +795,11 @@ impl core::fmt::Debug for PixmapRef<'_> {
 ///
 /// Can be created from `Pixmap` or from a user provided data.
 ///
-/// The data is not aligned, therefore width == stride.
-#[derive(PartialEq)]
+/// Rows can be padded or stored bottom-up. See `from_bytes_with_stride`.
 pub struct PixmapMut<'a> {
     data: &'a mut [u8],
     size: IntSize,
+    stride: isize, // in pixels, negative when rows are stored bottom-up
 }
 
 impl<'a> PixmapMut<'a> {
@@ -467,17 +819,36 @@ impl<'a> PixmapMut<'a> {
         Some(PixmapMut {
             data,
             size,
+            stride: width as isize,
+        })
+    }
+
+    /// Creates a new `PixmapMut` from bytes with an explicit row stride.
+    ///
+    /// Allows rendering directly into padded or bottom-up buffers,
+    /// like window surfaces, video frames or a part of a larger image.
+    ///
+    /// See `PixmapRef::from_bytes_with_stride` for details.
+    pub fn from_bytes_with_stride(
+        data: &'a mut [u8],
+        width: u32,
+        height: u32,
+        stride: isize,
+    ) -> Option<Self> {
+        let size = IntSize::from_wh(width, height)?;
+        let stride = stride_in_pixels(data.len(), size, stride)?;
+        Some(PixmapMut {
+            data,
+            size,
+            stride,
         })
     }
 
     /// Creates a new `Pixmap` from the current data.
     ///
-    /// Clones the underlying data.
+    /// Clones the underlying data. Padding is not preserved and rows are stored top-down.
     pub fn to_owned(&self) -> Pixmap {
-        Pixmap {
-            data: self.data.to_vec(),
-            size: self.size,
-        }
+        self.as_ref().to_owned()
     }
 
     /// Returns a container that references Pixmap's data.
@@ -485,9 +856,25 @@ impl<'a> PixmapMut<'a> {
         PixmapRef {
             data: &self.data,
             size: self.size,
+            stride: self.stride,
         }
     }
 
+    /// Returns a pixmap that references a part of the current one.
+    ///
+    /// Doesn't copy pixels. `rect` will be clipped by the pixmap's bounds.
+    ///
+    /// Returns `None` when `rect` is outside the pixmap.
+    pub fn sub_pixmap(&mut self, rect: IntRect) -> Option<PixmapMut<'_>> {
+        let rect = self.size.to_int_rect(0, 0).intersect(&rect)?.to_screen_int_rect()?;
+        let range = sub_pixmap_range(self.size, self.stride, rect)?;
+        Some(PixmapMut {
+            data: self.data.get_mut(range)?,
+            size: rect.size(),
+            stride: self.stride,
+        })
+    }
+
     /// Returns pixmap's width.
     #[inline]
     pub fn width(&self) -> u32 {
@@ -505,25 +892,64 @@ impl<'a> PixmapMut<'a> {
         self.size
     }
 
+    /// Returns the distance between rows in bytes.
+    ///
+    /// Negative when rows are stored bottom-up.
+    pub fn stride(&self) -> isize {
+        self.stride * BYTES_PER_PIXEL as isize
+    }
+
     /// Fills the entire pixmap with a specified color.
     pub fn fill(&mut self, color: Color) {
         let c = color.premultiply().to_color_u8();
-        for p in self.pixels_mut() {
-            *p = c;
+        for y in 0..self.height() {
+            // Rows are always in bounds.
+            for p in self.row_mut(y).unwrap() {
+                *p = c;
+            }
         }
     }
 
     /// Returns the mutable internal data.
     ///
     /// Byteorder: RGBA
+    ///
+    /// Includes row padding, when present. Use `stride` to locate rows.
     pub fn data_mut(&mut self) -> &mut [u8] {
         self.data
     }
 
     /// Returns a mutable slice of pixels.
+    ///
+    /// Includes row padding, when present. Use `row_mut` to access pixels by rows.
     pub fn pixels_mut(&mut self) -> &mut [PremultipliedColorU8] {
         bytemuck::cast_slice_mut(self.data_mut())
     }
+
+    /// Returns a mutable row of pixels.
+    ///
+    /// Returns `None` when `y` is out of bounds.
+    pub fn row_mut(&mut self, y: u32) -> Option<&mut [PremultipliedColorU8]> {
+        if y >= self.height() {
+            return None;
+        }
+
+        let start = self.offset(0, y as usize);
+        let width = self.width() as usize;
+        self.pixels_mut().get_mut(start..start + width)
+    }
+}
+
+impl PartialEq for PixmapRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.rows().eq(other.rows())
+    }
+}
+
+impl PartialEq for PixmapMut<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
 }
 
 impl core::fmt::Debug for PixmapMut<'_> {
@@ -560,3 +986,44 @@ fn data_len_for_size(size: IntSize) -> Option<usize> {
     let row_bytes = min_row_bytes(size)?;
     compute_data_len(size, row_bytes.get())
 }
+
+/// Checks that `data_len` bytes can hold `size` pixels with the specified stride.
+///
+/// Returns the stride in pixels.
+fn stride_in_pixels(data_len: usize, size: IntSize, stride: isize) -> Option<isize> {
+    let row_bytes = min_row_bytes(size)?.get();
+    let abs_stride = stride.checked_abs()? as usize;
+    if abs_stride < row_bytes || stride % BYTES_PER_PIXEL as isize != 0 {
+        return None;
+    }
+
+    if data_len < compute_data_len(size, abs_stride)? {
+        return None;
+    }
+
+    Some(stride / BYTES_PER_PIXEL as isize)
+}
+
+/// Returns an index of the first pixel in a row.
+#[inline(always)]
+fn row_offset(size: IntSize, stride: isize, y: usize) -> usize {
+    if stride >= 0 {
+        stride as usize * y
+    } else {
+        // Bottom-up.
+        (-stride) as usize * (size.height() as usize - 1 - y)
+    }
+}
+
+/// Returns a bytes range occupied by `rect`.
+fn sub_pixmap_range(
+    size: IntSize,
+    stride: isize,
+    rect: ScreenIntRect,
+) -> Option<core::ops::Range<usize>> {
+    // The first row in memory.
+    let y = if stride >= 0 { rect.top() } else { rect.bottom() - 1 };
+    let start = (row_offset(size, stride, y as usize) + rect.x() as usize) * BYTES_PER_PIXEL;
+    let len = compute_data_len(rect.size(), stride.checked_abs()? as usize * BYTES_PER_PIXEL)?;
+    Some(start..start.checked_add(len)?)
+}
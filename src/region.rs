@@ -0,0 +1,362 @@
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{ClipOp, FillRule, IntRect, LengthU32, Path, Transform};
+
+use crate::blitter::Blitter;
+
+// Any span and band within `-COORD_LIMIT..=COORD_LIMIT` fits into `IntRect`.
+const COORD_LIMIT: i32 = i32::MAX / 2;
+
+/// A set of non-overlapping integer rectangles.
+///
+/// Similar to `SkRegion`. Rectangles are stored in y-x banded order:
+/// sorted by top, then by left, with all rectangles in a band having the same top and bottom.
+/// Vertically adjacent bands with the same horizontal spans are merged,
+/// therefore the same area always has the same representation.
+///
+/// Coordinates are limited by the `-1073741823..=1073741823` range (`i32::MAX / 2`).
+/// Rectangles and paths outside of it are clipped.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Region {
+    rects: Vec<IntRect>,
+}
+
+impl Region {
+    /// Creates a new, empty region.
+    pub fn new() -> Self {
+        Region::default()
+    }
+
+    /// Creates a new region from a rectangle.
+    ///
+    /// The rectangle is clipped to the supported coordinates range.
+    pub fn from_rect(rect: IntRect) -> Self {
+        match clamp_rect(rect) {
+            Some(rect) => Region { rects: vec![rect] },
+            None => Region::new(),
+        }
+    }
+
+    /// Creates a new region from a path.
+    ///
+    /// The path is rasterized without anti-aliasing, just like `Pixmap::fill_path`.
+    /// `clip` must have non-negative coordinates.
+    ///
+    /// Returns `None` when `clip` is invalid or when the transform is invalid.
+    pub fn from_path(
+        path: &Path,
+        fill_rule: FillRule,
+        transform: Transform,
+        clip: IntRect,
+    ) -> Option<Self> {
+        let clip = clamp_rect(clip)?.to_screen_int_rect()?;
+
+        let transformed_path;
+        let path = if transform.is_identity() {
+            path
+        } else {
+            transformed_path = path.clone().transform(transform)?;
+            &transformed_path
+        };
+
+        let mut builder = RegionBuilder {
+            rects: Vec::new(),
+            band_start: 0,
+            row_y: 0,
+            row: Vec::new(),
+        };
+
+        // Filling fails only when the path is outside the clip.
+        let _ = crate::scan::path::fill_path(path, fill_rule, &clip, &mut builder);
+        builder.flush_row();
+
+        Some(Region { rects: builder.rects })
+    }
+
+    /// Checks that the region is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Checks that the region is a single rectangle.
+    pub fn is_rect(&self) -> bool {
+        self.rects.len() == 1
+    }
+
+    /// Returns region's rectangles in y-x banded order.
+    pub fn rects(&self) -> &[IntRect] {
+        &self.rects
+    }
+
+    /// Returns region's bounds.
+    ///
+    /// Returns `None` when the region is empty.
+    pub fn bounds(&self) -> Option<IntRect> {
+        let first = self.rects.first()?;
+        let last = self.rects.last()?;
+
+        let mut left = first.left();
+        let mut right = first.right();
+        for rect in &self.rects {
+            left = left.min(rect.left());
+            right = right.max(rect.right());
+        }
+
+        IntRect::from_ltrb(left, first.top(), right, last.bottom())
+    }
+
+    /// Checks that the region contains the specified point.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.rects.iter().any(|r| {
+            x >= r.left() && x < r.right() && y >= r.top() && y < r.bottom()
+        })
+    }
+
+    /// Checks that the region completely contains the specified rectangle.
+    pub fn contains_rect(&self, rect: IntRect) -> bool {
+        // A region cannot contain anything outside the supported range.
+        if clamp_rect(rect) != Some(rect) {
+            return false;
+        }
+
+        let mut rest = Region::from_rect(rect);
+        rest.combine(self, ClipOp::Difference);
+        rest.is_empty()
+    }
+
+    /// Checks that the region and the specified rectangle overlap.
+    pub fn intersects_rect(&self, rect: IntRect) -> bool {
+        self.rects.iter().any(|r| r.intersect(&rect).is_some())
+    }
+
+    /// Moves the region.
+    ///
+    /// Returns `None` when the region would leave the supported coordinates range.
+    /// The region is left unchanged in this case.
+    pub fn translate(&mut self, dx: i32, dy: i32) -> Option<()> {
+        let mut rects = Vec::with_capacity(self.rects.len());
+        for r in &self.rects {
+            let rect = IntRect::from_xywh(
+                r.x().checked_add(dx)?,
+                r.y().checked_add(dy)?,
+                r.width(),
+                r.height(),
+            )?;
+
+            if clamp_rect(rect) != Some(rect) {
+                return None;
+            }
+
+            rects.push(rect);
+        }
+
+        self.rects = rects;
+        Some(())
+    }
+
+    /// Combines the current region with another one.
+    pub fn combine(&mut self, other: &Region, op: ClipOp) {
+        self.rects = combine_rects(&self.rects, &other.rects, op);
+    }
+
+    /// Combines the current region with a rectangle.
+    ///
+    /// The rectangle is clipped to the supported coordinates range.
+    pub fn combine_rect(&mut self, rect: IntRect, op: ClipOp) {
+        self.combine(&Region::from_rect(rect), op);
+    }
+
+    /// Clears the region.
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+}
+
+
+type Span = (i32, i32);
+
+// Clips a rectangle to the supported coordinates range.
+//
+// Returns `None` when the rectangle is completely outside of it.
+fn clamp_rect(rect: IntRect) -> Option<IntRect> {
+    IntRect::from_ltrb(
+        rect.left().max(-COORD_LIMIT),
+        rect.top().max(-COORD_LIMIT),
+        rect.right().min(COORD_LIMIT),
+        rect.bottom().min(COORD_LIMIT),
+    )
+}
+
+fn combine_rects(a: &[IntRect], b: &[IntRect], op: ClipOp) -> Vec<IntRect> {
+    let mut ys = Vec::with_capacity((a.len() + b.len()) * 2);
+    for r in a.iter().chain(b.iter()) {
+        ys.push(r.top());
+        ys.push(r.bottom());
+    }
+    ys.sort_unstable();
+    ys.dedup();
+
+    let mut rects = Vec::new();
+    let mut band_start = 0;
+    let mut cursor_a = 0;
+    let mut cursor_b = 0;
+    let mut spans_a = Vec::new();
+    let mut spans_b = Vec::new();
+    let mut spans = Vec::new();
+    for w in ys.windows(2) {
+        let (top, bottom) = (w[0], w[1]);
+
+        band_spans(a, &mut cursor_a, top, bottom, &mut spans_a);
+        band_spans(b, &mut cursor_b, top, bottom, &mut spans_b);
+        combine_spans(&spans_a, &spans_b, op, &mut spans);
+
+        push_band(&mut rects, &mut band_start, top, bottom, &spans);
+    }
+
+    rects
+}
+
+// Collects horizontal spans of a banded region that cover the specified rows.
+//
+// Bands never cross the `top..bottom` range, since it was built from all bands edges.
+//
+// Ranges must be requested from top to bottom. `cursor` points to the first band
+// that can still be used and is moved forward, so each rect is skipped only once.
+fn band_spans(
+    rects: &[IntRect],
+    cursor: &mut usize,
+    top: i32,
+    bottom: i32,
+    spans: &mut Vec<Span>,
+) {
+    spans.clear();
+
+    // Bands are sorted and do not overlap, therefore their bottoms are sorted too.
+    while *cursor < rects.len() && rects[*cursor].bottom() <= top {
+        *cursor += 1;
+    }
+
+    for r in &rects[*cursor..] {
+        if r.top() >= bottom {
+            break;
+        }
+
+        if r.top() <= top && r.bottom() >= bottom {
+            spans.push((r.left(), r.right()));
+        }
+    }
+}
+
+// Both inputs must be sorted and non-overlapping.
+fn combine_spans(a: &[Span], b: &[Span], op: ClipOp, spans: &mut Vec<Span>) {
+    spans.clear();
+
+    let mut xs = Vec::with_capacity((a.len() + b.len()) * 2);
+    for s in a.iter().chain(b.iter()) {
+        xs.push(s.0);
+        xs.push(s.1);
+    }
+    xs.sort_unstable();
+    xs.dedup();
+
+    let mut ia = 0;
+    let mut ib = 0;
+    for w in xs.windows(2) {
+        let (left, right) = (w[0], w[1]);
+
+        while ia < a.len() && a[ia].1 <= left {
+            ia += 1;
+        }
+
+        while ib < b.len() && b[ib].1 <= left {
+            ib += 1;
+        }
+
+        let in_a = ia < a.len() && a[ia].0 <= left;
+        let in_b = ib < b.len() && b[ib].0 <= left;
+        let keep = match op {
+            ClipOp::Intersect => in_a && in_b,
+            ClipOp::Union => in_a || in_b,
+            ClipOp::Difference => in_a && !in_b,
+            ClipOp::Xor => in_a != in_b,
+        };
+
+        if keep {
+            match spans.last_mut() {
+                Some(last) if last.1 == left => last.1 = right,
+                _ => spans.push((left, right)),
+            }
+        }
+    }
+}
+
+// Appends a band, merging it with the previous one when possible.
+fn push_band(
+    rects: &mut Vec<IntRect>,
+    band_start: &mut usize,
+    top: i32,
+    bottom: i32,
+    spans: &[Span],
+) {
+    if spans.is_empty() {
+        return;
+    }
+
+    let prev = &rects[*band_start..];
+    let can_merge = !prev.is_empty()
+        && prev[0].bottom() == top
+        && prev.len() == spans.len()
+        && prev.iter().zip(spans).all(|(r, s)| r.left() == s.0 && r.right() == s.1);
+
+    // Spans and bands are never empty and, since all coordinates are limited
+    // by `COORD_LIMIT`, always fit into `IntRect`.
+    if can_merge {
+        for r in &mut rects[*band_start..] {
+            if let Some(merged) = IntRect::from_ltrb(r.left(), r.top(), r.right(), bottom) {
+                *r = merged;
+            }
+        }
+    } else {
+        *band_start = rects.len();
+        rects.extend(spans.iter().filter_map(|s| IntRect::from_ltrb(s.0, top, s.1, bottom)));
+    }
+}
+
+
+struct RegionBuilder {
+    rects: Vec<IntRect>,
+    band_start: usize,
+    row_y: u32,
+    row: Vec<Span>,
+}
+
+impl RegionBuilder {
+    fn flush_row(&mut self) {
+        let top = self.row_y as i32;
+        push_band(&mut self.rects, &mut self.band_start, top, top + 1, &self.row);
+        self.row.clear();
+    }
+}
+
+impl Blitter for RegionBuilder {
+    fn blit_h(&mut self, x: u32, y: u32, width: LengthU32) {
+        // Rows are blitted from top to bottom and spans from left to right.
+        if y != self.row_y {
+            self.flush_row();
+            self.row_y = y;
+        }
+
+        let left = x as i32;
+        let right = (x + width.get()) as i32;
+        match self.row.last_mut() {
+            Some(last) if last.1 == left => last.1 = right,
+            _ => self.row.push((left, right)),
+        }
+    }
+}
@@ -79,6 +79,8 @@ cfg_if::cfg_if! {
             fn floor(self) -> Self;
             fn powf(self, y: Self) -> Self;
             fn acos(self) -> Self;
+            fn atan2(self, other: Self) -> Self;
+            fn log2(self) -> Self;
         }
 
         impl FloatExt for f32 {
@@ -109,6 +111,12 @@ cfg_if::cfg_if! {
             fn acos(self) -> Self {
                 libm::acosf(self)
             }
+            fn atan2(self, other: Self) -> Self {
+                libm::atan2f(self, other)
+            }
+            fn log2(self) -> Self {
+                libm::log2f(self)
+            }
         }
 
         impl FloatExt for f64 {
@@ -139,6 +147,12 @@ cfg_if::cfg_if! {
             fn acos(self) -> Self {
                 libm::acos(self)
             }
+            fn atan2(self, other: Self) -> Self {
+                libm::atan2(self, other)
+            }
+            fn log2(self) -> Self {
+                libm::log2(self)
+            }
         }
     }
 }
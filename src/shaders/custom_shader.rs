@@ -0,0 +1,99 @@
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::sync::Arc;
+
+use crate::{Shader, Transform, PremultipliedColor};
+
+use crate::floating_point::NormalizedF32;
+use crate::pipeline::{self, RasterPipelineBuilder, CustomCtx};
+
+/// The number of pixels passed to `CustomShaderFn::shade` at once.
+pub const CUSTOM_SHADER_BATCH_SIZE: usize = pipeline::HIGHP_STAGE_WIDTH;
+
+/// A user-defined shading function.
+///
+/// Implemented for all `Fn(f32, f32) -> PremultipliedColor` closures,
+/// which will be called per pixel.
+pub trait CustomShaderFn: Send + Sync {
+    /// Calculates colors for a batch of pixels.
+    ///
+    /// `x` and `y` are pixel centers mapped to the shader coordinates.
+    ///
+    /// A batch always has `CUSTOM_SHADER_BATCH_SIZE` pixels,
+    /// even when only some of them will be drawn.
+    fn shade(
+        &self,
+        x: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        y: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        colors: &mut [PremultipliedColor; CUSTOM_SHADER_BATCH_SIZE],
+    );
+}
+
+impl<F> CustomShaderFn for F
+    where F: Fn(f32, f32) -> PremultipliedColor + Send + Sync
+{
+    fn shade(
+        &self,
+        x: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        y: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        colors: &mut [PremultipliedColor; CUSTOM_SHADER_BATCH_SIZE],
+    ) {
+        for i in 0..CUSTOM_SHADER_BATCH_SIZE {
+            colors[i] = self(x[i], y[i]);
+        }
+    }
+}
+
+
+/// A custom shader.
+///
+/// Evaluates a user-defined function. Always uses the high precision pipeline.
+#[derive(Clone)]
+pub struct CustomShader<'a> {
+    function: Arc<dyn CustomShaderFn + 'a>,
+    pub(crate) opacity: NormalizedF32,
+    pub(crate) transform: Transform,
+}
+
+impl<'a> CustomShader<'a> {
+    /// Creates a new custom shader.
+    ///
+    /// Returns `None` when `transform` is not invertible.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<F: CustomShaderFn + 'a>(function: F, transform: Transform) -> Option<Shader<'a>> {
+        transform.invert()?;
+
+        Some(Shader::Custom(CustomShader {
+            function: Arc::new(function),
+            opacity: NormalizedF32::ONE,
+            transform,
+        }))
+    }
+
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder<'a>) -> Option<()> {
+        let ts = self.transform.invert()?;
+
+        p.push(pipeline::Stage::SeedShader);
+        p.push_transform(ts);
+
+        p.ctx.custom = CustomCtx {
+            function: Some(self.function.clone()),
+            opacity: self.opacity.get(),
+        };
+        p.push(pipeline::Stage::Custom);
+
+        Some(())
+    }
+}
+
+impl core::fmt::Debug for CustomShader<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CustomShader")
+            .field("opacity", &self.opacity)
+            .field("transform", &self.transform)
+            .finish()
+    }
+}
@@ -9,10 +9,17 @@ use alloc::vec::Vec;
 use crate::{Color, Transform, SpreadMode};
 
 use crate::floating_point::NormalizedF32;
-use crate::pipeline::{self, EvenlySpaced2StopGradientCtx, GradientColor, GradientCtx};
+use crate::pipeline::{self, EvenlySpaced2StopGradientCtx, GradientColor, GradientCtx, GradientLutCtx};
 use crate::pipeline::RasterPipelineBuilder;
 use crate::scalar::Scalar;
 
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
+// Gradients with more user stops than this will be rendered using a lookup table,
+// since the `Gradient` stage has to loop over all stops for each pixel.
+const LUT_MIN_STOPS: usize = 8;
+
 // The default SCALAR_NEARLY_ZERO threshold of .0024 is too big and causes regressions for svg
 // gradients defined in the wild.
 pub const DEGENERATE_THRESHOLD: f32 = 1.0 / (1 << 15) as f32;
@@ -36,6 +43,229 @@ impl GradientStop {
 }
 
 
+/// A color space used for gradient colors interpolation.
+///
+/// Matches the CSS Color 4 `<color-interpolation-method>`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InterpolationColorSpace {
+    /// Gamma encoded sRGB, aka `in srgb`.
+    Srgb,
+    /// Linear sRGB, aka `in srgb-linear`.
+    LinearSrgb,
+    /// OKLab, aka `in oklab`.
+    Oklab,
+    /// OKLCH, aka `in oklch`.
+    ///
+    /// Hue is interpolated according to `GradientInterpolation::hue_interpolation`.
+    Oklch,
+}
+
+// `#[derive(Default)]` for enums requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for InterpolationColorSpace {
+    fn default() -> Self {
+        InterpolationColorSpace::Srgb
+    }
+}
+
+
+/// A hue interpolation method for polar color spaces.
+///
+/// Matches the CSS Color 4 `<hue-interpolation-method>`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HueInterpolation {
+    /// Use the shorter arc between two hues.
+    Shorter,
+    /// Use the longer arc between two hues.
+    Longer,
+    /// Hue is always increasing.
+    Increasing,
+    /// Hue is always decreasing.
+    Decreasing,
+}
+
+// `#[derive(Default)]` for enums requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for HueInterpolation {
+    fn default() -> Self {
+        HueInterpolation::Shorter
+    }
+}
+
+
+/// Controls how gradient colors are interpolated.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct GradientInterpolation {
+    /// A color space in which colors will be interpolated.
+    ///
+    /// Default: Srgb
+    pub color_space: InterpolationColorSpace,
+
+    /// A hue interpolation method.
+    ///
+    /// Used only by `InterpolationColorSpace::Oklch`.
+    ///
+    /// Default: Shorter
+    pub hue_interpolation: HueInterpolation,
+
+    /// Interpolate premultiplied colors.
+    ///
+    /// CSS and SVG 2 always interpolate premultiplied colors,
+    /// while Skia doesn't by default.
+    ///
+    /// Default: false
+    pub premultiplied: bool,
+}
+
+impl GradientInterpolation {
+    fn is_polar(&self) -> bool {
+        self.color_space == InterpolationColorSpace::Oklch
+    }
+
+    /// Converts stop colors into the interpolation color space.
+    fn convert_colors(&self, stops: &[GradientStop]) -> Vec<GradientColor> {
+        let mut colors: Vec<GradientColor> = stops.iter().map(|stop| {
+            let c = stop.color;
+            match self.color_space {
+                InterpolationColorSpace::Srgb => GradientColor::from(c),
+                InterpolationColorSpace::LinearSrgb => {
+                    GradientColor::new(
+                        srgb_to_linear(c.red()),
+                        srgb_to_linear(c.green()),
+                        srgb_to_linear(c.blue()),
+                        c.alpha(),
+                    )
+                }
+                InterpolationColorSpace::Oklab => {
+                    let (l, a, b) = srgb_to_oklab(c);
+                    GradientColor::new(l, a, b, c.alpha())
+                }
+                InterpolationColorSpace::Oklch => {
+                    let (l, a, b) = srgb_to_oklab(c);
+                    let chroma = (a * a + b * b).sqrt();
+                    let mut hue = b.atan2(a).to_degrees();
+                    if hue < 0.0 {
+                        hue += 360.0;
+                    }
+
+                    GradientColor::new(l, chroma, hue, c.alpha())
+                }
+            }
+        }).collect();
+
+        if self.is_polar() {
+            // An achromatic color has a powerless hue,
+            // so we're taking one from the closest chromatic neighbor.
+            let is_achromatic = |c: &GradientColor| c.g.is_nearly_zero_within_tolerance(1e-4);
+            for i in 0..colors.len() {
+                if is_achromatic(&colors[i]) {
+                    let prev = colors[..i].iter().rev().find(|c| !is_achromatic(c));
+                    let next = colors[i+1..].iter().find(|c| !is_achromatic(c));
+                    if let Some(c) = prev.or(next) {
+                        colors[i].b = c.b;
+                    }
+                }
+            }
+
+            self.adjust_hues(&mut colors);
+        }
+
+        if self.premultiplied {
+            for c in &mut colors {
+                c.r *= c.a;
+                c.g *= c.a;
+                if !self.is_polar() {
+                    c.b *= c.a;
+                }
+            }
+        }
+
+        colors
+    }
+
+    /// Adjusts hues so the interpolation between stops follows `hue_interpolation`.
+    ///
+    /// Hues must be in a 0..360 range.
+    fn adjust_hues(&self, colors: &mut [GradientColor]) {
+        for i in 1..colors.len() {
+            // The previous hue could be already adjusted, so we have to normalize it first.
+            let h1 = colors[i - 1].b;
+            let mut diff = colors[i].b - (h1 - 360.0 * (h1 / 360.0).floor());
+            match self.hue_interpolation {
+                HueInterpolation::Shorter => {
+                    if diff > 180.0 {
+                        diff -= 360.0;
+                    } else if diff < -180.0 {
+                        diff += 360.0;
+                    }
+                }
+                HueInterpolation::Longer => {
+                    if 0.0 < diff && diff < 180.0 {
+                        diff -= 360.0;
+                    } else if -180.0 < diff && diff <= 0.0 {
+                        diff += 360.0;
+                    }
+                }
+                HueInterpolation::Increasing => {
+                    if diff < 0.0 {
+                        diff += 360.0;
+                    }
+                }
+                HueInterpolation::Decreasing => {
+                    if diff > 0.0 {
+                        diff -= 360.0;
+                    }
+                }
+            }
+
+            colors[i].b = h1 + diff;
+        }
+    }
+
+    fn push_stages(&self, colors_are_opaque: bool, p: &mut RasterPipelineBuilder) {
+        if self.color_space == InterpolationColorSpace::Srgb {
+            if !self.premultiplied && !colors_are_opaque {
+                p.push(pipeline::Stage::Premultiply);
+            }
+
+            return;
+        }
+
+        if self.premultiplied && !colors_are_opaque {
+            if self.is_polar() {
+                p.push(pipeline::Stage::UnpremultiplyPolar);
+            } else {
+                p.push(pipeline::Stage::Unpremultiply);
+            }
+        }
+
+        match self.color_space {
+            InterpolationColorSpace::Srgb => {}
+            InterpolationColorSpace::LinearSrgb => {
+                p.push(pipeline::Stage::LinearToSrgb);
+            }
+            InterpolationColorSpace::Oklab => {
+                p.push(pipeline::Stage::OklabToLinearSrgb);
+                p.push(pipeline::Stage::LinearToSrgb);
+            }
+            InterpolationColorSpace::Oklch => {
+                p.push(pipeline::Stage::OklchToOklab);
+                p.push(pipeline::Stage::OklabToLinearSrgb);
+                p.push(pipeline::Stage::LinearToSrgb);
+            }
+        }
+
+        // Colors outside the sRGB gamut are simply clamped.
+        p.push(pipeline::Stage::Clamp0);
+        p.push(pipeline::Stage::ClampA);
+
+        if !colors_are_opaque {
+            p.push(pipeline::Stage::Premultiply);
+        }
+    }
+}
+
+
 #[derive(Clone, Debug)]
 pub struct Gradient {
     stops: Vec<GradientStop>,
@@ -44,6 +274,11 @@ pub struct Gradient {
     points_to_unit: Transform,
     pub(crate) colors_are_opaque: bool,
     has_uniform_stops: bool,
+    pub(crate) interpolation: GradientInterpolation,
+    // The number of stops provided by the caller, without the dummy ones.
+    user_stops_len: usize,
+    // Like Skia's cached gradient bitmap. Must be updated on each stops or interpolation change.
+    lut: Option<GradientLutCtx>,
 }
 
 impl Gradient {
@@ -55,6 +290,8 @@ impl Gradient {
     ) -> Self {
         debug_assert!(stops.len() > 1);
 
+        let user_stops_len = stops.len();
+
         // Note: we let the caller skip the first and/or last position.
         // i.e. pos[0] = 0.3, pos[1] = 0.7
         // In these cases, we insert dummy entries to ensure that the final data
@@ -92,14 +329,24 @@ impl Gradient {
             prev = curr;
         }
 
-        Gradient {
+        let mut gradient = Gradient {
             stops,
             tile_mode,
             transform,
             points_to_unit,
             colors_are_opaque,
             has_uniform_stops,
-        }
+            interpolation: GradientInterpolation::default(),
+            user_stops_len,
+            lut: None,
+        };
+        gradient.update_lut();
+        gradient
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: GradientInterpolation) {
+        self.interpolation = interpolation;
+        self.update_lut();
     }
 
     pub fn push_stages(
@@ -123,7 +370,12 @@ impl Gradient {
             SpreadMode::Repeat => {
                 p.push(pipeline::Stage::RepeatX1);
             }
-            SpreadMode::Pad => {
+            SpreadMode::Pad | SpreadMode::Decal => {
+                if self.tile_mode == SpreadMode::Decal {
+                    // Remember which pixels are outside the gradient, before clamping.
+                    p.push(pipeline::Stage::DecalX1);
+                }
+
                 if self.has_uniform_stops {
                     // We clamp only when the stops are evenly spaced.
                     // If not, there may be hard stops, and clamping ruins hard stops at 0 and/or 1.
@@ -138,114 +390,146 @@ impl Gradient {
         if self.stops.len() == 2 {
             debug_assert!(self.has_uniform_stops);
 
-            let c0 = self.stops[0].color;
-            let c1 = self.stops[1].color;
+            let colors = self.interpolation.convert_colors(&self.stops);
+            let c0 = colors[0];
+            let c1 = colors[1];
 
             p.ctx.evenly_spaced_2_stop_gradient = EvenlySpaced2StopGradientCtx {
                 factor: GradientColor::new(
-                    c1.red() - c0.red(),
-                    c1.green() - c0.green(),
-                    c1.blue() - c0.blue(),
-                    c1.alpha() - c0.alpha(),
+                    c1.r - c0.r,
+                    c1.g - c0.g,
+                    c1.b - c0.b,
+                    c1.a - c0.a,
                 ),
-                bias: GradientColor::from(c0),
+                bias: c0,
             };
 
             p.push(pipeline::Stage::EvenlySpaced2StopGradient);
+        } else if let Some(ref lut) = self.lut {
+            // The lookup stage will also interpolate between neighbor entries.
+            p.push(pipeline::Stage::GradientLut);
+            p.ctx.gradient_lut = lut.clone();
         } else {
-            // Unlike Skia, we do not support the `evenly_spaced_gradient` stage.
-            // In our case, there is no performance difference.
-
-            let mut ctx = GradientCtx::default();
-
-            // Note: In order to handle clamps in search, the search assumes
-            // a stop conceptually placed at -inf.
-            // Therefore, the max number of stops is `self.points.len()+1`.
-            //
-            // We also need at least 16 values for lowp pipeline.
-            ctx.factors.reserve((self.stops.len() + 1).max(16));
-            ctx.biases.reserve((self.stops.len() + 1).max(16));
-
-            ctx.t_values.reserve(self.stops.len() + 1);
-
-            // Remove the dummy stops inserted by Gradient::new
-            // because they are naturally handled by the search method.
-            let (first_stop, last_stop) = if self.stops.len() > 2 {
-                let first = if self.stops[0].color != self.stops[1].color { 0 } else { 1 };
-
-                let len = self.stops.len();
-                let last = if self.stops[len - 2].color != self.stops[len - 1].color {
-                    len - 1
-                } else {
-                    len - 2
-                };
-                (first, last)
-            } else {
-                (0, 1)
-            };
+            p.push(pipeline::Stage::Gradient);
+            p.ctx.gradient = self.gradient_ctx();
+        }
 
-            let mut t_l = self.stops[first_stop].position.get();
-            let mut c_l = GradientColor::from(self.stops[first_stop].color);
-            ctx.push_const_color(c_l);
-            ctx.t_values.push(NormalizedF32::ZERO);
-            // N.B. lastStop is the index of the last stop, not one after.
-            for i in first_stop..last_stop {
-                let t_r = self.stops[i + 1].position.get();
-                let c_r = GradientColor::from(self.stops[i + 1].color);
-                debug_assert!(t_l <= t_r);
-                if t_l < t_r {
-                    // For each stop we calculate a bias B and a scale factor F, such that
-                    // for any t between stops n and n+1, the color we want is B[n] + F[n]*t.
-                    let f = GradientColor::new(
-                        (c_r.r - c_l.r) / (t_r - t_l),
-                        (c_r.g - c_l.g) / (t_r - t_l),
-                        (c_r.b - c_l.b) / (t_r - t_l),
-                        (c_r.a - c_l.a) / (t_r - t_l),
-                    );
-                    ctx.factors.push(f);
-
-                    ctx.biases.push(
-                        GradientColor::new(
-                            c_l.r - f.r * t_l,
-                            c_l.g - f.g * t_l,
-                            c_l.b - f.b * t_l,
-                            c_l.a - f.a * t_l,
-                        )
-                    );
-
-                    ctx.t_values.push(NormalizedF32::new_bounded(t_l));
-                }
+        self.interpolation.push_stages(self.colors_are_opaque, p);
 
-                t_l = t_r;
-                c_l = c_r;
-            }
+        if self.tile_mode == SpreadMode::Decal {
+            p.push(pipeline::Stage::CheckDecalMask);
+        }
 
-            ctx.push_const_color(c_l);
-            ctx.t_values.push(NormalizedF32::new_bounded(t_l));
+        push_stages_post(p);
 
-            ctx.len = ctx.factors.len();
+        Some(())
+    }
 
-            // All lists must have the same length.
-            debug_assert_eq!(ctx.factors.len(), ctx.t_values.len());
-            debug_assert_eq!(ctx.biases.len(), ctx.t_values.len());
+    fn gradient_ctx(&self) -> GradientCtx {
+        // Unlike Skia, we do not support the `evenly_spaced_gradient` stage.
+        // In our case, there is no performance difference.
 
-            // Will with zeros until we have enough data to fit into F32x16.
-            while ctx.factors.len() < 16 {
-                ctx.factors.push(GradientColor::default());
-                ctx.biases.push(GradientColor::default());
+        let colors = self.interpolation.convert_colors(&self.stops);
+
+        let mut ctx = GradientCtx::default();
+
+        // Note: In order to handle clamps in search, the search assumes
+        // a stop conceptually placed at -inf.
+        // Therefore, the max number of stops is `self.points.len()+1`.
+        //
+        // We also need at least 16 values for lowp pipeline.
+        ctx.factors.reserve((self.stops.len() + 1).max(16));
+        ctx.biases.reserve((self.stops.len() + 1).max(16));
+
+        ctx.t_values.reserve(self.stops.len() + 1);
+
+        // Remove the dummy stops inserted by Gradient::new
+        // because they are naturally handled by the search method.
+        let (first_stop, last_stop) = if self.stops.len() > 2 {
+            let first = if self.stops[0].color != self.stops[1].color { 0 } else { 1 };
+
+            let len = self.stops.len();
+            let last = if self.stops[len - 2].color != self.stops[len - 1].color {
+                len - 1
+            } else {
+                len - 2
+            };
+            (first, last)
+        } else {
+            (0, 1)
+        };
+
+        let mut t_l = self.stops[first_stop].position.get();
+        let mut c_l = colors[first_stop];
+        ctx.push_const_color(c_l);
+        ctx.t_values.push(NormalizedF32::ZERO);
+        // N.B. lastStop is the index of the last stop, not one after.
+        for i in first_stop..last_stop {
+            let t_r = self.stops[i + 1].position.get();
+            let c_r = colors[i + 1];
+            debug_assert!(t_l <= t_r);
+            if t_l < t_r {
+                // For each stop we calculate a bias B and a scale factor F, such that
+                // for any t between stops n and n+1, the color we want is B[n] + F[n]*t.
+                let f = GradientColor::new(
+                    (c_r.r - c_l.r) / (t_r - t_l),
+                    (c_r.g - c_l.g) / (t_r - t_l),
+                    (c_r.b - c_l.b) / (t_r - t_l),
+                    (c_r.a - c_l.a) / (t_r - t_l),
+                );
+                ctx.factors.push(f);
+
+                ctx.biases.push(
+                    GradientColor::new(
+                        c_l.r - f.r * t_l,
+                        c_l.g - f.g * t_l,
+                        c_l.b - f.b * t_l,
+                        c_l.a - f.a * t_l,
+                    )
+                );
+
+                ctx.t_values.push(NormalizedF32::new_bounded(t_l));
             }
 
-            p.push(pipeline::Stage::Gradient);
-            p.ctx.gradient = ctx;
+            t_l = t_r;
+            c_l = c_r;
         }
 
-        if !self.colors_are_opaque {
-            p.push(pipeline::Stage::Premultiply);
+        ctx.push_const_color(c_l);
+        ctx.t_values.push(NormalizedF32::new_bounded(t_l));
+
+        ctx.len = ctx.factors.len();
+
+        // All lists must have the same length.
+        debug_assert_eq!(ctx.factors.len(), ctx.t_values.len());
+        debug_assert_eq!(ctx.biases.len(), ctx.t_values.len());
+
+        // Will with zeros until we have enough data to fit into F32x16.
+        while ctx.factors.len() < 16 {
+            ctx.factors.push(GradientColor::default());
+            ctx.biases.push(GradientColor::default());
         }
 
-        push_stages_post(p);
+        ctx
+    }
 
-        Some(())
+    fn update_lut(&mut self) {
+        self.lut = if self.user_stops_len > LUT_MIN_STOPS {
+            Some(GradientLutCtx::new(&self.gradient_ctx(), self.lut_size()))
+        } else {
+            None
+        };
+    }
+
+    // 256 entries are enough for smooth gradients, but hard stops and closely placed stops
+    // require a higher resolution, otherwise they will be smeared.
+    fn lut_size(&self) -> usize {
+        let has_fine_details = self.stops.windows(2).any(|w| {
+            let d = w[1].position.get() - w[0].position.get();
+            d < 4.0 / 256.0 && w[0].color != w[1].color
+        });
+
+        if has_fine_details { 1024 } else { 256 }
     }
 
     pub fn apply_opacity(&mut self, opacity: f32) {
@@ -254,5 +538,137 @@ impl Gradient {
         }
 
         self.colors_are_opaque = self.stops.iter().all(|p| p.color.is_opaque());
+        self.update_lut();
+    }
+}
+
+pub fn average_gradient_color(points: &[GradientStop]) -> Color {
+    use crate::wide::f32x4;
+
+    fn load_color(c: Color) -> f32x4 {
+        f32x4::from([c.red(), c.green(), c.blue(), c.alpha()])
+    }
+
+    fn store_color(c: f32x4) -> Color {
+        let c: [f32; 4] = c.into();
+        Color::from_rgba(c[0], c[1], c[2], c[3]).unwrap()
+    }
+
+    assert!(!points.is_empty());
+
+    // The gradient is a piecewise linear interpolation between colors. For a given interval,
+    // the integral between the two endpoints is 0.5 * (ci + cj) * (pj - pi), which provides that
+    // intervals average color. The overall average color is thus the sum of each piece. The thing
+    // to keep in mind is that the provided gradient definition may implicitly use p=0 and p=1.
+    let mut blend = f32x4::default();
+
+    // Bake 1/(colorCount - 1) uniform stop difference into this scale factor
+    let w_scale = f32x4::splat(0.5);
+
+    for i in 0..points.len()-1 {
+        // Calculate the average color for the interval between pos(i) and pos(i+1)
+        let c0 = load_color(points[i].color);
+        let c1 = load_color(points[i + 1].color);
+        // when pos == null, there are colorCount uniformly distributed stops, going from 0 to 1,
+        // so pos[i + 1] - pos[i] = 1/(colorCount-1)
+        let w = points[i + 1].position.get() - points[i].position.get();
+        blend += w_scale * f32x4::splat(w) * (c1 + c0);
+    }
+
+    // Now account for any implicit intervals at the start or end of the stop definitions
+    if points[0].position.get() > 0.0 {
+        // The first color is fixed between p = 0 to pos[0], so 0.5 * (ci + cj) * (pj - pi)
+        // becomes 0.5 * (c + c) * (pj - 0) = c * pj
+        let c = load_color(points[0].color);
+        blend += f32x4::splat(points[0].position.get()) * c;
+    }
+
+    let last_idx = points.len() - 1;
+    if points[last_idx].position.get() < 1.0 {
+        // The last color is fixed between pos[n-1] to p = 1, so 0.5 * (ci + cj) * (pj - pi)
+        // becomes 0.5 * (c + c) * (1 - pi) = c * (1 - pi)
+        let c = load_color(points[last_idx].color);
+        blend += (f32x4::splat(1.0) - f32x4::splat(points[last_idx].position.get())) * c;
+    }
+
+    store_color(blend)
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// From https://bottosson.github.io/posts/oklab/
+fn srgb_to_oklab(c: Color) -> (f32, f32, f32) {
+    let r = srgb_to_linear(c.red());
+    let g = srgb_to_linear(c.green());
+    let b = srgb_to_linear(c.blue());
+
+    let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
+    let m = 0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b;
+    let s = 0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b;
+
+    // sRGB colors are always in gamut, so LMS values are never negative.
+    let l = l.powf(1.0 / 3.0);
+    let m = m.powf(1.0 / 3.0);
+    let s = s.powf(1.0 / 3.0);
+
+    (
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjust_hues(hue_interpolation: HueInterpolation, hues: &[f32]) -> Vec<f32> {
+        let interpolation = GradientInterpolation {
+            color_space: InterpolationColorSpace::Oklch,
+            hue_interpolation,
+            premultiplied: false,
+        };
+
+        let mut colors: Vec<_> = hues.iter().map(|h| GradientColor::new(0.5, 0.1, *h, 1.0)).collect();
+        interpolation.adjust_hues(&mut colors);
+        colors.iter().map(|c| c.b).collect()
+    }
+
+    #[test]
+    fn shorter_hues() {
+        assert_eq!(
+            adjust_hues(HueInterpolation::Shorter, &[10.0, 200.0, 30.0, 220.0]),
+            &[10.0, -160.0, -330.0, -500.0]
+        );
+    }
+
+    #[test]
+    fn longer_hues() {
+        assert_eq!(
+            adjust_hues(HueInterpolation::Longer, &[10.0, 100.0, 200.0, 300.0]),
+            &[10.0, -260.0, -520.0, -780.0]
+        );
+    }
+
+    #[test]
+    fn increasing_hues() {
+        assert_eq!(
+            adjust_hues(HueInterpolation::Increasing, &[300.0, 100.0, 300.0, 100.0]),
+            &[300.0, 460.0, 660.0, 820.0]
+        );
+    }
+
+    #[test]
+    fn decreasing_hues() {
+        assert_eq!(
+            adjust_hues(HueInterpolation::Decreasing, &[300.0, 100.0, 300.0, 100.0]),
+            &[300.0, 100.0, -60.0, -260.0]
+        );
     }
 }
@@ -0,0 +1,116 @@
+// Copyright 2013 Google Inc.
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{Pixmap, PixmapRef, PremultipliedColorU8};
+
+/// Lazily built mipmap levels of a pixmap.
+///
+/// Shared between pattern clones and all pipelines created for a single draw,
+/// so levels are built only once.
+#[derive(Default, Debug)]
+pub(crate) struct MipmapCache {
+    #[cfg(feature = "std")]
+    levels: std::sync::Mutex<Vec<Arc<Pixmap>>>,
+}
+
+impl MipmapCache {
+    /// Returns mipmap levels for a pixmap, building the missing ones.
+    ///
+    /// The original pixmap is level 0 and is not included.
+    /// Each next level is twice as small, up to 1x1 or up to `max_levels`.
+    ///
+    /// `pixmap` must be the same on each call.
+    #[cfg(feature = "std")]
+    pub fn levels(&self, pixmap: PixmapRef, max_levels: usize) -> Vec<Arc<Pixmap>> {
+        match self.levels.lock() {
+            Ok(mut levels) => {
+                build_levels(pixmap, &mut levels, max_levels);
+                levels.iter().take(max_levels).cloned().collect()
+            }
+            Err(_) => {
+                let mut levels = Vec::new();
+                build_levels(pixmap, &mut levels, max_levels);
+                levels
+            }
+        }
+    }
+
+    /// Returns mipmap levels for a pixmap.
+    ///
+    /// Without `std`, there is nothing to synchronize the cache with,
+    /// so levels are built each time.
+    #[cfg(not(feature = "std"))]
+    pub fn levels(&self, pixmap: PixmapRef, max_levels: usize) -> Vec<Arc<Pixmap>> {
+        let mut levels = Vec::new();
+        build_levels(pixmap, &mut levels, max_levels);
+        levels
+    }
+}
+
+// Appends mipmap levels until there are `max_levels` of them or the last one is 1x1.
+fn build_levels(pixmap: PixmapRef, levels: &mut Vec<Arc<Pixmap>>, max_levels: usize) {
+    while levels.len() < max_levels {
+        let prev = match levels.last() {
+            Some(level) => Pixmap::as_ref(level),
+            None => pixmap,
+        };
+
+        if prev.width() == 1 && prev.height() == 1 {
+            break;
+        }
+
+        let level = downsample(prev);
+        levels.push(Arc::new(level));
+    }
+}
+
+// A simple box filter.
+//
+// When a dimension is odd, the last pixel in a row/column will be
+// merged into the previous one, so we will not lose any data.
+fn downsample(src: PixmapRef) -> Pixmap {
+    let width = core::cmp::max(1, src.width() / 2);
+    let height = core::cmp::max(1, src.height() / 2);
+    let mut dst = Pixmap::new(width, height).unwrap();
+
+    let dst_width = width as usize;
+
+    let range = |i: u32, dst_len: u32, src_len: u32| {
+        let start = i * 2;
+        let end = if i + 1 == dst_len { src_len } else { start + 2 };
+        (start as usize, end as usize)
+    };
+
+    for y in 0..height {
+        let (y0, y1) = range(y, height, src.height());
+        for x in 0..width {
+            let (x0, x1) = range(x, width, src.width());
+
+            let mut sum = [0u32; 4];
+            for sy in y0..y1 {
+                // Rows are always in bounds.
+                for c in &src.row(sy as u32).unwrap()[x0..x1] {
+                    sum[0] += u32::from(c.red());
+                    sum[1] += u32::from(c.green());
+                    sum[2] += u32::from(c.blue());
+                    sum[3] += u32::from(c.alpha());
+                }
+            }
+
+            let count = ((x1 - x0) * (y1 - y0)) as u32;
+            let avg = |v: u32| ((v + count / 2) / count) as u8;
+            // Averaging premultiplied colors always produces a valid premultiplied color.
+            dst.pixels_mut()[y as usize * dst_width + x as usize] = PremultipliedColorU8::from_rgba(
+                avg(sum[0]), avg(sum[1]), avg(sum[2]), avg(sum[3]),
+            ).unwrap();
+        }
+    }
+
+    dst
+}
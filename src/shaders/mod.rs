@@ -4,20 +4,29 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
+mod custom_shader;
 mod gradient;
 mod linear_gradient;
+mod mipmap;
 mod radial_gradient;
 mod pattern;
+mod perlin_noise;
+mod picture_shader;
 
-pub use gradient::GradientStop;
+pub use custom_shader::{CustomShader, CustomShaderFn, CUSTOM_SHADER_BATCH_SIZE};
+pub use gradient::{GradientStop, GradientInterpolation, InterpolationColorSpace, HueInterpolation};
 pub use linear_gradient::LinearGradient;
 pub use radial_gradient::RadialGradient;
-pub use pattern::{Pattern, FilterQuality, PixmapPaint};
+pub use pattern::{Pattern, FilterQuality, CubicResampler, PixmapPaint};
+pub use perlin_noise::PerlinNoise;
+pub use picture_shader::PictureShader;
 
-use crate::{Color, Transform};
+use alloc::boxed::Box;
+
+use crate::{Color, Transform, BlendMode, PixmapRef};
 
 use crate::floating_point::NormalizedF32;
-use crate::pipeline::RasterPipelineBuilder;
+use crate::pipeline::{self, RasterPipelineBuilder, ShaderPipeline, ShaderPixmap};
 use crate::scalar::Scalar;
 
 
@@ -34,6 +43,11 @@ pub enum SpreadMode {
 
     /// Repeat the shader's image horizontally and vertically.
     Repeat,
+
+    /// Draw nothing outside of the shader's original bounds.
+    ///
+    /// Produces transparent pixels instead.
+    Decal,
 }
 
 impl Default for SpreadMode {
@@ -61,6 +75,48 @@ pub enum Shader<'a> {
     RadialGradient(RadialGradient),
     /// A pattern shader.
     Pattern(Pattern<'a>),
+    /// A Perlin noise shader.
+    PerlinNoise(PerlinNoise),
+    /// A picture shader.
+    Picture(PictureShader<'a>),
+    /// A custom shader.
+    Custom(CustomShader<'a>),
+    /// A shader that blends two other shaders.
+    ///
+    /// Essentially a `SkShaders::Blend`.
+    Blend {
+        /// A shader used as the source.
+        src: Box<Shader<'a>>,
+        /// A shader used as the destination.
+        dst: Box<Shader<'a>>,
+        /// A blending mode used to combine shaders.
+        mode: BlendMode,
+    },
+    /// A shader that linearly interpolates between two other shaders.
+    ///
+    /// Essentially a `SkShaders::Lerp`.
+    Lerp {
+        /// A shader returned when `t` is 0.
+        a: Box<Shader<'a>>,
+        /// A shader returned when `t` is 1.
+        b: Box<Shader<'a>>,
+        /// An interpolation factor.
+        ///
+        /// Will be clamped to the 0..=1 range.
+        t: f32,
+    },
+    /// A shader with an additional transform.
+    ///
+    /// The transform is applied after the shader's own one, but before
+    /// the one passed to the drawing method.
+    ///
+    /// Essentially a `SkShader::makeWithLocalMatrix`.
+    WithLocalMatrix {
+        /// A transformed shader.
+        shader: Box<Shader<'a>>,
+        /// A local transform.
+        transform: Transform,
+    },
 }
 
 impl<'a> Shader<'a> {
@@ -71,13 +127,29 @@ impl<'a> Shader<'a> {
             Shader::LinearGradient(ref g) => g.is_opaque(),
             Shader::RadialGradient(_) => false,
             Shader::Pattern(_) => false,
+            Shader::PerlinNoise(_) => false,
+            Shader::Picture(_) => false,
+            Shader::Custom(_) => false,
+            Shader::Blend { .. } => false,
+            Shader::Lerp { ref a, ref b, .. } => a.is_opaque() && b.is_opaque(),
+            Shader::WithLocalMatrix { ref shader, .. } => shader.is_opaque(),
+        }
+    }
+
+    /// Returns a pixmap that should be passed to a pipeline with this shader's stages.
+    pub(crate) fn pixmap_src(&self) -> Option<PixmapRef<'a>> {
+        match self {
+            Shader::Pattern(ref patt) => Some(patt.pixmap),
+            Shader::WithLocalMatrix { ref shader, .. } => shader.pixmap_src(),
+            // Blend and Lerp children are evaluated by their own pipelines.
+            _ => None,
         }
     }
 
     // Unlike Skia, we do not have is_constant, because we don't have Color shaders.
 
     /// If this returns false, then we draw nothing (do not fall back to shader context)
-    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder) -> Option<()> {
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder<'a>) -> Option<()> {
         match self {
             Shader::SolidColor(color) => {
                 p.push_uniform_color(color.premultiply());
@@ -86,9 +158,52 @@ impl<'a> Shader<'a> {
             Shader::LinearGradient(ref g) => g.push_stages(p),
             Shader::RadialGradient(ref g) => g.push_stages(p),
             Shader::Pattern(ref patt) => patt.push_stages(p),
+            Shader::PerlinNoise(ref noise) => noise.push_stages(p),
+            Shader::Picture(ref picture) => picture.push_stages(p),
+            Shader::Custom(ref custom) => custom.push_stages(p),
+            Shader::Blend { ref src, ref dst, mode } => {
+                // Blend using a separate pipeline, otherwise the blending mode context
+                // could be overwritten by the paint's one.
+                let mut blend = RasterPipelineBuilder::new();
+                blend.ctx.compose.src = Some(Box::new(src.compile()?));
+                blend.ctx.compose.dst = Some(Box::new(dst.compile()?));
+                blend.push(pipeline::Stage::ComposeShaders);
+                mode.push_stages(&mut blend);
+
+                // Just a dummy one.
+                let pixmap_src = PixmapRef::from_bytes(&[0, 0, 0, 0], 1, 1).unwrap();
+                p.ctx.compose.src = Some(Box::new(blend.compile_shader(ShaderPixmap::Borrowed(pixmap_src))));
+                p.ctx.compose.dst = None;
+                p.push(pipeline::Stage::ComposeShaders);
+
+                Some(())
+            }
+            Shader::Lerp { ref a, ref b, t } => {
+                p.ctx.compose.src = Some(Box::new(b.compile()?));
+                p.ctx.compose.dst = Some(Box::new(a.compile()?));
+                p.ctx.compose.t = t.bound(0.0, 1.0);
+                p.push(pipeline::Stage::ComposeShaders);
+                p.push(pipeline::Stage::LerpShaders);
+                Some(())
+            }
+            Shader::WithLocalMatrix { ref shader, transform } => {
+                let mut shader = Shader::clone(shader);
+                shader.transform(*transform);
+                shader.push_stages(p)
+            }
         }
     }
 
+    // Compiles the shader into a separate pipeline.
+    fn compile(&self) -> Option<ShaderPipeline<'a>> {
+        let mut p = RasterPipelineBuilder::new();
+        self.push_stages(&mut p)?;
+        let pixmap_src = self.pixmap_src()
+            // Just a dummy one.
+            .unwrap_or_else(|| PixmapRef::from_bytes(&[0, 0, 0, 0], 1, 1).unwrap());
+        Some(p.compile_shader(ShaderPixmap::Borrowed(pixmap_src)))
+    }
+
     /// Transforms the shader.
     pub fn transform(&mut self, ts: Transform) {
         match self {
@@ -102,6 +217,56 @@ impl<'a> Shader<'a> {
             Shader::Pattern(p) => {
                 p.transform = p.transform.post_concat(ts);
             }
+            Shader::PerlinNoise(n) => {
+                n.transform = n.transform.post_concat(ts);
+            }
+            Shader::Picture(p) => {
+                p.transform = p.transform.post_concat(ts);
+            }
+            Shader::Custom(c) => {
+                c.transform = c.transform.post_concat(ts);
+            }
+            Shader::Blend { src, dst, .. } => {
+                src.transform(ts);
+                dst.transform(ts);
+            }
+            Shader::Lerp { a, b, .. } => {
+                a.transform(ts);
+                b.transform(ts);
+            }
+            Shader::WithLocalMatrix { transform, .. } => {
+                *transform = transform.post_concat(ts);
+            }
+        }
+    }
+
+    /// Sets gradient colors interpolation.
+    ///
+    /// Does nothing for non-gradient shaders.
+    pub fn set_gradient_interpolation(&mut self, interpolation: GradientInterpolation) {
+        match self {
+            Shader::SolidColor(_) => {}
+            Shader::LinearGradient(g) => {
+                g.base.set_interpolation(interpolation);
+            }
+            Shader::RadialGradient(g) => {
+                g.base.set_interpolation(interpolation);
+            }
+            Shader::Pattern(_) => {}
+            Shader::PerlinNoise(_) => {}
+            Shader::Picture(_) => {}
+            Shader::Custom(_) => {}
+            Shader::Blend { src, dst, .. } => {
+                src.set_gradient_interpolation(interpolation);
+                dst.set_gradient_interpolation(interpolation);
+            }
+            Shader::Lerp { a, b, .. } => {
+                a.set_gradient_interpolation(interpolation);
+                b.set_gradient_interpolation(interpolation);
+            }
+            Shader::WithLocalMatrix { shader, .. } => {
+                shader.set_gradient_interpolation(interpolation);
+            }
         }
     }
 
@@ -111,13 +276,17 @@ impl<'a> Shader<'a> {
     ///
     /// This is roughly the same as Skia's `SkPaint::setAlpha`.
     ///
-    /// Unlike Skia, we do not support global alpha/opacity, which is in Skia
-    /// is set via the alpha channel of the `SkPaint::fColor4f`.
-    /// Instead, you can shift the opacity of the shader to whatever value you need.
+    /// Modifies the shader itself. To change the opacity of any shader
+    /// without modifying it, use `Paint::opacity` instead.
     ///
     /// - For `SolidColor` this function will multiply `color.alpha` by `opacity`.
     /// - For gradients this function will multiply all colors by `opacity`.
     /// - For `Pattern` this function will multiply `Patter::opacity` by `opacity`.
+    /// - For `PerlinNoise` this function will multiply the resulting alpha by `opacity`.
+    /// - For `Picture` this function will multiply `PictureShader::opacity` by `opacity`.
+    /// - For `Custom` this function will multiply the resulting colors by `opacity`.
+    /// - For `Lerp` and `WithLocalMatrix` this function will be applied to the children.
+    /// - For `Blend` this function will interpolate the result with a transparent color.
     pub fn apply_opacity(&mut self, opacity: f32) {
         match self {
             Shader::SolidColor(ref mut c) => {
@@ -132,6 +301,32 @@ impl<'a> Shader<'a> {
             Shader::Pattern(ref mut p) => {
                 p.opacity = NormalizedF32::new(p.opacity.get() * opacity.bound(0.0, 1.0)).unwrap();
             }
+            Shader::PerlinNoise(ref mut n) => {
+                n.opacity = NormalizedF32::new(n.opacity.get() * opacity.bound(0.0, 1.0)).unwrap();
+            }
+            Shader::Picture(ref mut p) => {
+                p.opacity = NormalizedF32::new(p.opacity.get() * opacity.bound(0.0, 1.0)).unwrap();
+            }
+            Shader::Custom(ref mut c) => {
+                c.opacity = NormalizedF32::new(c.opacity.get() * opacity.bound(0.0, 1.0)).unwrap();
+            }
+            Shader::Blend { .. } => {
+                // Unlike other shaders, we cannot apply opacity to the children,
+                // since this will affect the blending itself.
+                let blend = core::mem::replace(self, Shader::SolidColor(Color::TRANSPARENT));
+                *self = Shader::Lerp {
+                    a: Box::new(Shader::SolidColor(Color::TRANSPARENT)),
+                    b: Box::new(blend),
+                    t: opacity.bound(0.0, 1.0),
+                };
+            }
+            Shader::Lerp { a, b, .. } => {
+                a.apply_opacity(opacity);
+                b.apply_opacity(opacity);
+            }
+            Shader::WithLocalMatrix { shader, .. } => {
+                shader.apply_opacity(opacity);
+            }
         }
     }
 }
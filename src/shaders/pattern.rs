@@ -4,11 +4,17 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
-use crate::{Shader, Transform, PixmapRef, SpreadMode, BlendMode};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+
+use crate::{Shader, Transform, Pixmap, PixmapRef, SpreadMode, BlendMode, ColorFilter};
 
 use crate::floating_point::NormalizedF32;
+use crate::geom::ScreenIntRect;
 use crate::pipeline;
-use crate::pipeline::RasterPipelineBuilder;
+use crate::pipeline::{RasterPipelineBuilder, ShaderPipeline, ShaderPixmap};
+
+use super::mipmap::MipmapCache;
 
 #[cfg(all(not(feature = "std"), feature = "libm"))]
 use crate::scalar::FloatExt;
@@ -20,15 +26,69 @@ pub enum FilterQuality {
     Nearest,
     /// Bilinear.
     Bilinear,
+    /// Bilinear with mipmaps.
+    ///
+    /// When a pixmap is downscaled, two nearest mipmap levels will be sampled
+    /// and interpolated. Prevents aliasing, but mipmaps generation is expensive.
+    Trilinear,
     /// Bicubic. High quality, but slow.
+    ///
+    /// The same as `Cubic(CubicResampler::MITCHELL)`.
     Bicubic,
+    /// A cubic filter with custom B and C values.
+    Cubic(CubicResampler),
+    /// Lanczos with a radius of 3 pixels.
+    ///
+    /// Sharper than cubic filters, but even slower.
+    Lanczos3,
+}
+
+
+/// A cubic resampler.
+///
+/// Defined by the B and C values from the "Reconstruction Filters in Computer Graphics"
+/// paper by Don P. Mitchell and Arun N. Netravali.
+/// Lower B values produce sharper images, while higher C values produce more ringing.
+///
+/// Essentially a `SkCubicResampler`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CubicResampler {
+    /// The B value.
+    pub b: f32,
+    /// The C value.
+    pub c: f32,
+}
+
+impl CubicResampler {
+    /// Mitchell filter. B = 1/3, C = 1/3.
+    ///
+    /// Used by `FilterQuality::Bicubic`.
+    pub const MITCHELL: Self = CubicResampler { b: 1.0 / 3.0, c: 1.0 / 3.0 };
+
+    /// Catmull-Rom filter. B = 0, C = 1/2.
+    ///
+    /// Sharper than Mitchell.
+    pub const CATMULL_ROM: Self = CubicResampler { b: 0.0, c: 1.0 / 2.0 };
+
+    // Returns weight polynomial coefficients for each of the 4 taps,
+    // where `t` is a sample point offset from the second tap center.
+    fn coefficients(&self) -> [[f32; 4]; 4] {
+        let b = self.b;
+        let c = self.c;
+        [
+            [b / 6.0, -b / 2.0 - c, b / 2.0 + 2.0 * c, -b / 6.0 - c],
+            [1.0 - b / 3.0, 0.0, -3.0 + 2.0 * b + c, 2.0 - 1.5 * b - c],
+            [b / 6.0, b / 2.0 + c, 3.0 - 2.5 * b - 2.0 * c, -2.0 + 1.5 * b + c],
+            [0.0, 0.0, -c, b / 6.0 + c],
+        ]
+    }
 }
 
 
 /// Controls how a pixmap should be blended.
 ///
 /// Like `Paint`, but for `Pixmap`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct PixmapPaint {
     /// Pixmap opacity.
     ///
@@ -46,6 +106,20 @@ pub struct PixmapPaint {
     ///
     /// Default: Nearest
     pub quality: FilterQuality,
+
+    /// Forbids sampling pixels outside of the source rectangle.
+    ///
+    /// Used only by `draw_pixmap_rect`. Useful for sprite sheets,
+    /// where filtering would otherwise bleed neighbor sprites.
+    /// Disables mipmaps.
+    ///
+    /// Default: false
+    pub strict: bool,
+
+    /// A color filter applied to the pixmap.
+    ///
+    /// Default: None
+    pub color_filter: Option<ColorFilter>,
 }
 
 impl Default for PixmapPaint {
@@ -54,6 +128,8 @@ impl Default for PixmapPaint {
             opacity: 1.0,
             blend_mode: BlendMode::default(),
             quality: FilterQuality::Nearest,
+            strict: false,
+            color_filter: None,
         }
     }
 }
@@ -63,15 +139,20 @@ impl Default for PixmapPaint {
 ///
 /// Essentially a `SkImageShader`.
 ///
-/// Unlike Skia, we do not support FilterQuality::Medium, because it involves
-/// mipmap generation, which adds too much complexity.
+/// Mipmaps for `FilterQuality::Trilinear` are generated on demand,
+/// only when the pattern is downscaled, and are shared between clones.
 #[derive(Clone, Debug)]
 pub struct Pattern<'a> {
     pub(crate) pixmap: PixmapRef<'a>,
     quality: FilterQuality,
-    spread_mode: SpreadMode,
+    spread_mode_x: SpreadMode,
+    spread_mode_y: SpreadMode,
+    // Used by `draw_pixmap_rect` in strict mode.
+    // Only `SpreadMode::Pad` is supported.
+    pub(crate) subset: Option<ScreenIntRect>,
     pub(crate) opacity: NormalizedF32,
     pub(crate) transform: Transform,
+    mipmaps: Arc<MipmapCache>,
 }
 
 impl<'a> Pattern<'a> {
@@ -86,22 +167,80 @@ impl<'a> Pattern<'a> {
         opacity: f32,
         transform: Transform,
     ) -> Shader {
+        Self::new_with_tile_modes(pixmap, spread_mode, spread_mode, quality, opacity, transform)
+    }
+
+    /// Creates a new pattern shader with separate horizontal and vertical spread modes.
+    ///
+    /// For example, `SpreadMode::Repeat` and `SpreadMode::Decal` will produce
+    /// a horizontal strip, which is transparent above and below.
+    ///
+    /// `opacity` will be clamped to the 0..=1 range.
+    pub fn new_with_tile_modes(
+        pixmap: PixmapRef<'a>,
+        spread_mode_x: SpreadMode,
+        spread_mode_y: SpreadMode,
+        quality: FilterQuality,
+        opacity: f32,
+        transform: Transform,
+    ) -> Shader<'a> {
         Shader::Pattern(Pattern {
             pixmap,
-            spread_mode,
+            spread_mode_x,
+            spread_mode_y,
+            subset: None,
             quality,
             opacity: NormalizedF32::new_bounded(opacity),
             transform,
+            mipmaps: Arc::new(MipmapCache::default()),
         })
     }
 
-    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder) -> Option<()> {
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder<'a>) -> Option<()> {
         let ts = self.transform.invert()?;
+        let quality = self.adjusted_quality(ts);
 
-        p.push(pipeline::Stage::SeedShader);
+        if quality == FilterQuality::Trilinear {
+            return self.push_trilinear_stages(ts, p);
+        }
 
-        p.push_transform(ts);
+        self.push_sampler_stages(ts, quality, p);
+
+        Some(())
+    }
+
+    /// Compiles a pattern that uses a shared pixmap into a separate pipeline.
+    ///
+    /// Unlike `push_stages`, a pipeline will own the pixmap.
+    /// Mipmaps are not supported, so `FilterQuality::Trilinear` will fallback to `Bilinear`.
+    pub(crate) fn compile_shared<'b>(
+        pixmap: Arc<Pixmap>,
+        spread_mode: SpreadMode,
+        quality: FilterQuality,
+        opacity: f32,
+        transform: Transform,
+    ) -> Option<ShaderPipeline<'b>> {
+        let quality = if quality == FilterQuality::Trilinear { FilterQuality::Bilinear } else { quality };
 
+        let pattern = Pattern {
+            pixmap: Pixmap::as_ref(&pixmap),
+            quality,
+            spread_mode_x: spread_mode,
+            spread_mode_y: spread_mode,
+            subset: None,
+            opacity: NormalizedF32::new_bounded(opacity),
+            transform,
+            mipmaps: Arc::new(MipmapCache::default()),
+        };
+
+        let ts = transform.invert()?;
+        let mut p = RasterPipelineBuilder::new();
+        pattern.push_sampler_stages(ts, pattern.adjusted_quality(ts), &mut p);
+        Some(p.compile_shader(ShaderPixmap::Shared(pixmap.clone())))
+    }
+
+    // `ts` is an inverted pattern transform.
+    fn adjusted_quality(&self, ts: Transform) -> FilterQuality {
         let mut quality = self.quality;
 
         if ts.is_identity() || ts.is_translate() {
@@ -117,43 +256,76 @@ impl<'a> Pattern<'a> {
             }
         }
 
-        // TODO: minimizing scale via mipmap
+        quality
+    }
+
+    // Unlike `push_stages`, doesn't store the pixmap, so a builder can have any lifetime.
+    fn push_sampler_stages(&self, ts: Transform, quality: FilterQuality, p: &mut RasterPipelineBuilder) {
+        p.push(pipeline::Stage::SeedShader);
+
+        p.push_transform(ts);
+
+        p.ctx.limit_x = pipeline::TileCtx {
+            scale: self.pixmap.width() as f32,
+            inv_scale: 1.0 / self.pixmap.width() as f32,
+        };
+
+        p.ctx.limit_y = pipeline::TileCtx {
+            scale: self.pixmap.height() as f32,
+            inv_scale: 1.0 / self.pixmap.height() as f32,
+        };
+
+        let subset = self.subset.unwrap_or_else(|| self.pixmap.size().to_screen_int_rect(0, 0));
+        p.ctx.sampler = pipeline::SamplerCtx {
+            spread_mode_x: self.spread_mode_x,
+            spread_mode_y: self.spread_mode_y,
+            inv_width: 1.0 / self.pixmap.width() as f32,
+            inv_height: 1.0 / self.pixmap.height() as f32,
+            subset_left: subset.left() as f32,
+            subset_top: subset.top() as f32,
+            subset_right: subset.right() as f32,
+            subset_bottom: subset.bottom() as f32,
+            ..pipeline::SamplerCtx::default()
+        };
+
+        let decal_x = self.spread_mode_x == SpreadMode::Decal;
+        let decal_y = self.spread_mode_y == SpreadMode::Decal;
+        match (decal_x, decal_y) {
+            (true, true) => p.push(pipeline::Stage::DecalXAndY),
+            (true, false) => p.push(pipeline::Stage::DecalX),
+            (false, true) => p.push(pipeline::Stage::DecalY),
+            (false, false) => {}
+        }
 
         match quality {
             FilterQuality::Nearest => {
-                p.ctx.limit_x = pipeline::TileCtx {
-                    scale: self.pixmap.width() as f32,
-                    inv_scale: 1.0 / self.pixmap.width() as f32,
-                };
-
-                p.ctx.limit_y = pipeline::TileCtx {
-                    scale: self.pixmap.height() as f32,
-                    inv_scale: 1.0 / self.pixmap.height() as f32,
-                };
-
-                match self.spread_mode {
-                    SpreadMode::Pad => { /* The gather() stage will clamp for us. */ }
-                    SpreadMode::Repeat => p.push(pipeline::Stage::Repeat),
-                    SpreadMode::Reflect => p.push(pipeline::Stage::Reflect),
+                match self.spread_mode_x {
+                    SpreadMode::Pad | SpreadMode::Decal => { /* The gather() stage will clamp for us. */ }
+                    SpreadMode::Repeat => p.push(pipeline::Stage::RepeatX),
+                    SpreadMode::Reflect => p.push(pipeline::Stage::ReflectX),
+                }
+
+                match self.spread_mode_y {
+                    SpreadMode::Pad | SpreadMode::Decal => {}
+                    SpreadMode::Repeat => p.push(pipeline::Stage::RepeatY),
+                    SpreadMode::Reflect => p.push(pipeline::Stage::ReflectY),
                 }
 
                 p.push(pipeline::Stage::Gather);
             }
-            FilterQuality::Bilinear => {
-                p.ctx.sampler = pipeline::SamplerCtx {
-                    spread_mode: self.spread_mode,
-                    inv_width: 1.0 / self.pixmap.width() as f32,
-                    inv_height: 1.0 / self.pixmap.height() as f32,
-                };
+            // Mipmap levels are sampled using bilinear filtering.
+            FilterQuality::Bilinear | FilterQuality::Trilinear => {
                 p.push(pipeline::Stage::Bilinear);
             }
-            FilterQuality::Bicubic => {
-                p.ctx.sampler = pipeline::SamplerCtx {
-                    spread_mode: self.spread_mode,
-                    inv_width: 1.0 / self.pixmap.width() as f32,
-                    inv_height: 1.0 / self.pixmap.height() as f32,
-                };
-                p.push(pipeline::Stage::Bicubic);
+            FilterQuality::Bicubic | FilterQuality::Cubic(_) | FilterQuality::Lanczos3 => {
+                match quality {
+                    FilterQuality::Cubic(resampler) => {
+                        p.ctx.sampler.cubic_coeffs = resampler.coefficients();
+                        p.push(pipeline::Stage::Cubic);
+                    }
+                    FilterQuality::Lanczos3 => p.push(pipeline::Stage::Lanczos3),
+                    _ => p.push(pipeline::Stage::Bicubic),
+                }
 
                 // Bicubic filtering naturally produces out of range values on both sides of [0,1].
                 p.push(pipeline::Stage::Clamp0);
@@ -161,13 +333,92 @@ impl<'a> Pattern<'a> {
             }
         }
 
+        if decal_x || decal_y {
+            p.push(pipeline::Stage::CheckDecalMask);
+        }
+
+        self.push_opacity_stages(p);
+    }
+
+    fn push_opacity_stages(&self, p: &mut RasterPipelineBuilder) {
         // Unlike Skia, we do not support global opacity and only Pattern allows it.
         if self.opacity != NormalizedF32::ONE {
             debug_assert_eq!(core::mem::size_of_val(&self.opacity), 4, "alpha must be f32");
             p.ctx.current_coverage = self.opacity.get();
             p.push(pipeline::Stage::Scale1Float);
         }
+    }
+
+    // `ts` is an inverted pattern transform.
+    fn push_trilinear_stages(&self, ts: Transform, p: &mut RasterPipelineBuilder<'a>) -> Option<()> {
+        // How many pixmap pixels are covered by a single device pixel.
+        // We're using the largest scale, so we would never alias.
+        let scale_x = (ts.sx * ts.sx + ts.ky * ts.ky).sqrt();
+        let scale_y = (ts.kx * ts.kx + ts.sy * ts.sy).sqrt();
+        let level = scale_x.max(scale_y).log2();
+
+        // Not downscaled.
+        if !(level > 0.0) {
+            self.push_sampler_stages(ts, FilterQuality::Bilinear, p);
+            return Some(());
+        }
+
+        // Build only the levels we will actually sample.
+        let lower = level.floor() as usize;
+        let mipmaps = self.mipmaps.levels(self.pixmap, lower + 1);
+
+        // A 1x1 pixmap has no mipmaps.
+        if mipmaps.is_empty() {
+            self.push_sampler_stages(ts, FilterQuality::Bilinear, p);
+            return Some(());
+        }
+
+        if lower >= mipmaps.len() {
+            // Cannot be downscaled any further.
+            p.ctx.compose.src = Some(Box::new(self.compile_level(&mipmaps, mipmaps.len())?));
+            p.ctx.compose.dst = None;
+            p.push(pipeline::Stage::ComposeShaders);
+        } else {
+            // Mipmap levels are owned by us, which is not supported by the pipeline.
+            // So we have to sample them using separate pipelines.
+            p.ctx.compose.dst = Some(Box::new(self.compile_level(&mipmaps, lower)?));
+            p.ctx.compose.src = Some(Box::new(self.compile_level(&mipmaps, lower + 1)?));
+            p.ctx.compose.t = level - lower as f32;
+            p.push(pipeline::Stage::ComposeShaders);
+            p.push(pipeline::Stage::LerpShaders);
+        }
+
+        self.push_opacity_stages(p);
 
         Some(())
     }
+
+    // Level 0 is `pixmap`, therefore not stored in `mipmaps`.
+    fn compile_level(&self, mipmaps: &[Arc<Pixmap>], index: usize) -> Option<ShaderPipeline<'a>> {
+        let (pixmap, shader_pixmap) = if index == 0 {
+            (self.pixmap, ShaderPixmap::Borrowed(self.pixmap))
+        } else {
+            let level = &mipmaps[index - 1];
+            (Pixmap::as_ref(level), ShaderPixmap::Shared(level.clone()))
+        };
+
+        // Map level pixels to the original pixmap ones.
+        let sx = self.pixmap.width() as f32 / pixmap.width() as f32;
+        let sy = self.pixmap.height() as f32 / pixmap.height() as f32;
+
+        let level = Pattern {
+            pixmap,
+            quality: FilterQuality::Bilinear,
+            spread_mode_x: self.spread_mode_x,
+            spread_mode_y: self.spread_mode_y,
+            subset: None,
+            opacity: NormalizedF32::ONE,
+            transform: self.transform.pre_scale(sx, sy),
+            mipmaps: self.mipmaps.clone(),
+        };
+
+        let mut p = RasterPipelineBuilder::new();
+        level.push_sampler_stages(level.transform.invert()?, FilterQuality::Bilinear, &mut p);
+        Some(p.compile_shader(shader_pixmap))
+    }
 }
@@ -0,0 +1,383 @@
+// Copyright 2013 Google Inc.
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+// Based on the SVG 1.1 `feTurbulence` reference implementation:
+// https://www.w3.org/TR/SVG11/filters.html#feTurbulenceElement
+
+use alloc::vec::Vec;
+
+use crate::{Shader, Rect, Transform};
+
+use crate::floating_point::NormalizedF32;
+use crate::scalar::Scalar;
+use crate::pipeline::{self, RasterPipelineBuilder, PerlinNoiseCtx, StitchData};
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
+const BLOCK_SIZE: usize = 0x100;
+const BLOCK_MASK: i32 = 0xff;
+const PERLIN_N: i32 = 0x1000;
+// Each octave has half the impact of the previous one and samples the noise
+// at twice the coordinates. After 24 octaves, the impact is below `f32` precision,
+// while coordinates start to overflow `i32` and `f32`, which produces garbage.
+const MAX_OCTAVES: u32 = 24;
+
+const RAND_M: i64 = 2147483647; // 2**31 - 1
+const RAND_A: i64 = 16807; // 7**5; primitive root of m
+const RAND_Q: i64 = 127773; // m / a
+const RAND_R: i64 = 2836; // m % a
+
+/// A Perlin noise shader.
+///
+/// Implements the SVG `feTurbulence` filter primitive.
+///
+/// Essentially a `SkPerlinNoiseShader`.
+#[derive(Clone, Debug)]
+pub struct PerlinNoise {
+    fractal_noise: bool,
+    base_frequency_x: f32,
+    base_frequency_y: f32,
+    num_octaves: u32,
+    stitch: Option<StitchData>,
+    lattice_selector: Vec<u8>,
+    gradients: Vec<[f32; 2]>,
+    pub(crate) opacity: NormalizedF32,
+    pub(crate) transform: Transform,
+}
+
+impl PerlinNoise {
+    /// Creates a new fractal noise shader.
+    ///
+    /// Produces colors in a -1..1 range, mapped to the 0..1 range.
+    ///
+    /// `seed` is truncated to an integer, just like in SVG.
+    ///
+    /// When `stitch_tile` is set, base frequencies will be adjusted
+    /// so the noise would be seamlessly tiled by the specified rectangle.
+    ///
+    /// `num_octaves` is limited by 24, since further octaves do not affect the result.
+    ///
+    /// Returns `None` when:
+    ///
+    /// - any of the base frequencies is negative or not finite
+    /// - `seed` is not finite
+    /// - `transform` is not invertible
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_fractal_noise(
+        base_frequency_x: f32,
+        base_frequency_y: f32,
+        num_octaves: u32,
+        seed: f32,
+        stitch_tile: Option<Rect>,
+        transform: Transform,
+    ) -> Option<Shader<'static>> {
+        Self::new(true, base_frequency_x, base_frequency_y, num_octaves, seed, stitch_tile, transform)
+    }
+
+    /// Creates a new turbulence shader.
+    ///
+    /// Unlike fractal noise, sums absolute noise values, which produces
+    /// colors in a 0..1 range.
+    ///
+    /// See [`new_fractal_noise`](#method.new_fractal_noise) for details.
+    pub fn new_turbulence(
+        base_frequency_x: f32,
+        base_frequency_y: f32,
+        num_octaves: u32,
+        seed: f32,
+        stitch_tile: Option<Rect>,
+        transform: Transform,
+    ) -> Option<Shader<'static>> {
+        Self::new(false, base_frequency_x, base_frequency_y, num_octaves, seed, stitch_tile, transform)
+    }
+
+    #[allow(clippy::new_ret_no_self)]
+    fn new(
+        fractal_noise: bool,
+        mut base_frequency_x: f32,
+        mut base_frequency_y: f32,
+        num_octaves: u32,
+        seed: f32,
+        stitch_tile: Option<Rect>,
+        transform: Transform,
+    ) -> Option<Shader<'static>> {
+        if !(base_frequency_x.is_finite() && base_frequency_x >= 0.0) {
+            return None;
+        }
+
+        if !(base_frequency_y.is_finite() && base_frequency_y >= 0.0) {
+            return None;
+        }
+
+        if !seed.is_finite() {
+            return None;
+        }
+
+        transform.invert()?;
+
+        let num_octaves = num_octaves.min(MAX_OCTAVES);
+
+        let mut stitch = None;
+        if let Some(tile) = stitch_tile {
+            // When stitching tiled turbulence, the frequencies must be adjusted
+            // so that the tile borders will be continuous.
+            base_frequency_x = adjust_frequency(base_frequency_x, tile.width());
+            base_frequency_y = adjust_frequency(base_frequency_y, tile.height());
+
+            let width = (tile.width() * base_frequency_x + 0.5) as i32;
+            let height = (tile.height() * base_frequency_y + 0.5) as i32;
+            stitch = Some(StitchData {
+                width,
+                height,
+                wrap_x: ((tile.x() * base_frequency_x) as i32).wrapping_add(PERLIN_N).wrapping_add(width),
+                wrap_y: ((tile.y() * base_frequency_y) as i32).wrapping_add(PERLIN_N).wrapping_add(height),
+            });
+        }
+
+        let (lattice_selector, gradients) = init_tables(seed as i64);
+
+        Some(Shader::PerlinNoise(PerlinNoise {
+            fractal_noise,
+            base_frequency_x,
+            base_frequency_y,
+            num_octaves,
+            stitch,
+            lattice_selector,
+            gradients,
+            opacity: NormalizedF32::ONE,
+            transform,
+        }))
+    }
+
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder) -> Option<()> {
+        let ts = self.transform.invert()?;
+
+        p.push(pipeline::Stage::SeedShader);
+        p.push_transform(ts);
+
+        p.ctx.perlin_noise = PerlinNoiseCtx {
+            fractal_noise: self.fractal_noise,
+            base_frequency_x: self.base_frequency_x,
+            base_frequency_y: self.base_frequency_y,
+            num_octaves: self.num_octaves,
+            stitch: self.stitch,
+            lattice_selector: self.lattice_selector.clone(),
+            gradients: self.gradients.clone(),
+            opacity: self.opacity.get(),
+        };
+        p.push(pipeline::Stage::PerlinNoise);
+
+        // Noise is generated as an unpremultiplied color.
+        p.push(pipeline::Stage::Premultiply);
+
+        Some(())
+    }
+}
+
+fn adjust_frequency(freq: f32, tile_size: f32) -> f32 {
+    if freq == 0.0 {
+        return freq;
+    }
+
+    let lo_freq = (tile_size * freq).floor() / tile_size;
+    let hi_freq = (tile_size * freq).ceil() / tile_size;
+    if freq / lo_freq < hi_freq / freq {
+        lo_freq
+    } else {
+        hi_freq
+    }
+}
+
+fn random(seed: i64) -> i64 {
+    let mut result = RAND_A * (seed % RAND_Q) - RAND_R * (seed / RAND_Q);
+    if result <= 0 {
+        result += RAND_M;
+    }
+
+    result
+}
+
+fn init_tables(mut seed: i64) -> (Vec<u8>, Vec<[f32; 2]>) {
+    // The seed value clamp to the range [1, RAND_M - 1].
+    if seed <= 0 {
+        seed = -(seed % (RAND_M - 1)) + 1;
+    }
+
+    if seed > RAND_M - 1 {
+        seed = RAND_M - 1;
+    }
+
+    let mut lattice_selector = Vec::with_capacity(BLOCK_SIZE + BLOCK_SIZE + 2);
+    let mut gradients = Vec::with_capacity(4 * BLOCK_SIZE);
+    for _ in 0..4 {
+        for i in 0..BLOCK_SIZE {
+            if lattice_selector.len() < BLOCK_SIZE {
+                lattice_selector.push(i as u8);
+            }
+
+            let mut gradient = [0.0f64; 2];
+            for v in &mut gradient {
+                seed = random(seed);
+                *v = ((seed % (BLOCK_SIZE + BLOCK_SIZE) as i64) - BLOCK_SIZE as i64) as f64
+                    / BLOCK_SIZE as f64;
+            }
+
+            let s = (gradient[0] * gradient[0] + gradient[1] * gradient[1]).sqrt();
+            // Zero-length gradients are possible and SVG doesn't handle them,
+            // so we have to avoid a division by zero.
+            if s != 0.0 {
+                gradient[0] /= s;
+                gradient[1] /= s;
+            }
+
+            gradients.push([gradient[0] as f32, gradient[1] as f32]);
+        }
+    }
+
+    for i in (1..BLOCK_SIZE).rev() {
+        seed = random(seed);
+        let j = (seed % BLOCK_SIZE as i64) as usize;
+        lattice_selector.swap(i, j);
+    }
+
+    for i in 0..BLOCK_SIZE + 2 {
+        let v = lattice_selector[i];
+        lattice_selector.push(v);
+    }
+
+    (lattice_selector, gradients)
+}
+
+impl PerlinNoiseCtx {
+    // Used by the `PerlinNoise` stage.
+    //
+    // Returns an unpremultiplied RGBA color.
+    pub(crate) fn turbulence(&self, x: f32, y: f32) -> [f32; 4] {
+        let mut stitch = self.stitch;
+        let mut sum = [0.0; 4];
+        let mut vec = [x * self.base_frequency_x, y * self.base_frequency_y];
+        let mut ratio = 1.0;
+        for _ in 0..self.num_octaves {
+            let noise = self.noise2(vec, stitch.as_ref());
+            for (s, n) in sum.iter_mut().zip(noise.iter()) {
+                if self.fractal_noise {
+                    *s += n / ratio;
+                } else {
+                    *s += n.abs() / ratio;
+                }
+            }
+
+            vec[0] *= 2.0;
+            vec[1] *= 2.0;
+            ratio *= 2.0;
+
+            if let Some(ref mut stitch) = stitch {
+                // Update stitch values. Subtracting PerlinN before the multiplication and
+                // adding it afterward simplifies to subtracting it once.
+                //
+                // Values will overflow after ~20 octaves. Since lattice points are masked
+                // by `BLOCK_MASK` anyway, wrapping is fine.
+                stitch.width = stitch.width.wrapping_mul(2);
+                stitch.wrap_x = stitch.wrap_x.wrapping_mul(2).wrapping_sub(PERLIN_N);
+                stitch.height = stitch.height.wrapping_mul(2);
+                stitch.wrap_y = stitch.wrap_y.wrapping_mul(2).wrapping_sub(PERLIN_N);
+            }
+        }
+
+        for s in &mut sum {
+            if self.fractal_noise {
+                *s = (*s + 1.0) * 0.5;
+            }
+
+            *s = s.bound(0.0, 1.0);
+        }
+
+        sum[3] *= self.opacity;
+        sum
+    }
+
+    fn noise2(&self, vec: [f32; 2], stitch: Option<&StitchData>) -> [f32; 4] {
+        let t = vec[0] + PERLIN_N as f32;
+        let mut bx0 = t as i32;
+        let mut bx1 = bx0.wrapping_add(1);
+        let rx0 = t - bx0 as f32;
+        let rx1 = rx0 - 1.0;
+
+        let t = vec[1] + PERLIN_N as f32;
+        let mut by0 = t as i32;
+        let mut by1 = by0.wrapping_add(1);
+        let ry0 = t - by0 as f32;
+        let ry1 = ry0 - 1.0;
+
+        // If stitching, adjust lattice points accordingly.
+        //
+        // Unlike the reference implementation, we're doing this before masking,
+        // otherwise stitching will never happen. Just like Skia.
+        if let Some(stitch) = stitch {
+            if bx0 >= stitch.wrap_x {
+                bx0 = bx0.wrapping_sub(stitch.width);
+            }
+
+            if bx1 >= stitch.wrap_x {
+                bx1 = bx1.wrapping_sub(stitch.width);
+            }
+
+            if by0 >= stitch.wrap_y {
+                by0 = by0.wrapping_sub(stitch.height);
+            }
+
+            if by1 >= stitch.wrap_y {
+                by1 = by1.wrapping_sub(stitch.height);
+            }
+        }
+
+        let bx0 = (bx0 & BLOCK_MASK) as usize;
+        let bx1 = (bx1 & BLOCK_MASK) as usize;
+        let by0 = (by0 & BLOCK_MASK) as usize;
+        let by1 = (by1 & BLOCK_MASK) as usize;
+
+        let lattice = &self.lattice_selector;
+        let i = lattice[bx0] as usize;
+        let j = lattice[bx1] as usize;
+        let b00 = lattice[i + by0] as usize;
+        let b10 = lattice[j + by0] as usize;
+        let b01 = lattice[i + by1] as usize;
+        let b11 = lattice[j + by1] as usize;
+
+        let sx = s_curve(rx0);
+        let sy = s_curve(ry0);
+
+        let mut result = [0.0; 4];
+        for (channel, r) in result.iter_mut().enumerate() {
+            let gradients = &self.gradients[channel * BLOCK_SIZE..];
+
+            let q = gradients[b00];
+            let u = rx0 * q[0] + ry0 * q[1];
+            let q = gradients[b10];
+            let v = rx1 * q[0] + ry0 * q[1];
+            let a = lerp(sx, u, v);
+
+            let q = gradients[b01];
+            let u = rx0 * q[0] + ry1 * q[1];
+            let q = gradients[b11];
+            let v = rx1 * q[0] + ry1 * q[1];
+            let b = lerp(sx, u, v);
+
+            *r = lerp(sy, a, b);
+        }
+
+        result
+    }
+}
+
+fn s_curve(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
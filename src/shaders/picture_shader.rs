@@ -0,0 +1,167 @@
+// Copyright 2014 Google Inc.
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+
+use crate::{Shader, Transform, Pixmap, Picture, Rect, SpreadMode, FilterQuality};
+
+use crate::floating_point::NormalizedF32;
+use crate::pipeline::{self, RasterPipelineBuilder};
+use super::Pattern;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+use crate::scalar::FloatExt;
+
+// Just like Skia.
+const MAX_TILE_AREA: f32 = 2048.0 * 2048.0;
+
+/// A picture shader.
+///
+/// Renders a picture into a tile and then repeats it like `Pattern`.
+/// Unlike a pre-rendered `Pixmap`, the tile is rendered using the current device scale,
+/// therefore the output will not be blurred when zoomed in.
+///
+/// The rendered tile is cached and reused while the device scale stays the same.
+/// Clones share the same cache. Caching requires the `std` feature,
+/// otherwise the tile is rendered once per drawing operation.
+///
+/// Essentially a `SkPictureShader`.
+#[derive(Clone, Debug)]
+pub struct PictureShader<'a> {
+    picture: Arc<Picture<'a>>,
+    tile: Rect,
+    spread_mode: SpreadMode,
+    quality: FilterQuality,
+    pub(crate) opacity: NormalizedF32,
+    pub(crate) transform: Transform,
+    tile_cache: Arc<TileCache>,
+}
+
+impl<'a> PictureShader<'a> {
+    /// Creates a new picture shader.
+    ///
+    /// `tile` is a picture area that will be repeated.
+    /// Picture bounds will be used when not set.
+    ///
+    /// `opacity` will be clamped to the 0..=1 range.
+    ///
+    /// Returns `None` when `transform` is not invertible.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        picture: Arc<Picture<'a>>,
+        tile: Option<Rect>,
+        spread_mode: SpreadMode,
+        quality: FilterQuality,
+        opacity: f32,
+        transform: Transform,
+    ) -> Option<Shader<'a>> {
+        transform.invert()?;
+
+        let tile = tile.unwrap_or_else(|| picture.bounds());
+
+        Some(Shader::Picture(PictureShader {
+            picture,
+            tile,
+            spread_mode,
+            quality,
+            opacity: NormalizedF32::new_bounded(opacity),
+            transform,
+            tile_cache: Arc::new(TileCache::default()),
+        }))
+    }
+
+    pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder<'a>) -> Option<()> {
+        let ts = self.transform;
+
+        // How many device pixels are covered by a single tile unit.
+        let scale_x = (ts.sx * ts.sx + ts.ky * ts.ky).sqrt();
+        let scale_y = (ts.kx * ts.kx + ts.sy * ts.sy).sqrt();
+
+        let mut width = (self.tile.width() * scale_x).ceil();
+        let mut height = (self.tile.height() * scale_y).ceil();
+
+        // Prevent huge allocations when zoomed in too much.
+        // The tile will be simply upscaled in this case.
+        let area = width * height;
+        if area > MAX_TILE_AREA {
+            let k = (MAX_TILE_AREA / area).sqrt();
+            width = (width * k).floor();
+            height = (height * k).floor();
+        }
+
+        let width = width.max(1.0);
+        let height = height.max(1.0);
+
+        let tile = match self.tile_cache.get(width as u32, height as u32) {
+            Some(tile) => tile,
+            None => {
+                let mut tile = Pixmap::new(width as u32, height as u32)?;
+                let tile_ts = Transform::from_scale(width / self.tile.width(), height / self.tile.height())
+                    .pre_translate(-self.tile.x(), -self.tile.y());
+                tile.draw_picture(&self.picture, tile_ts, None);
+
+                let tile = Arc::new(tile);
+                self.tile_cache.set(tile.clone());
+                tile
+            }
+        };
+
+        // Map tile pixels back to the picture coordinates.
+        let patt_ts = self.transform
+            .pre_translate(self.tile.x(), self.tile.y())
+            .pre_scale(self.tile.width() / width, self.tile.height() / height);
+
+        // The tile is owned by us, which is not supported by the pipeline.
+        // So we have to sample it using a separate one.
+        p.ctx.compose.src = Some(Box::new(Pattern::compile_shared(
+            tile,
+            self.spread_mode,
+            self.quality,
+            self.opacity.get(),
+            patt_ts,
+        )?));
+        p.ctx.compose.dst = None;
+        p.push(pipeline::Stage::ComposeShaders);
+
+        Some(())
+    }
+}
+
+
+// The last rendered tile.
+//
+// Tile size depends only on the device scale, so it's used as a key.
+#[derive(Default, Debug)]
+struct TileCache {
+    #[cfg(feature = "std")]
+    tile: std::sync::Mutex<Option<Arc<Pixmap>>>,
+}
+
+impl TileCache {
+    #[cfg(feature = "std")]
+    fn get(&self, width: u32, height: u32) -> Option<Arc<Pixmap>> {
+        let tile = self.tile.lock().ok()?;
+        tile.as_ref()
+            .filter(|tile| tile.width() == width && tile.height() == height)
+            .cloned()
+    }
+
+    #[cfg(feature = "std")]
+    fn set(&self, tile: Arc<Pixmap>) {
+        if let Ok(mut cached) = self.tile.lock() {
+            *cached = Some(tile);
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn get(&self, _: u32, _: u32) -> Option<Arc<Pixmap>> {
+        None
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn set(&self, _: Arc<Pixmap>) {}
+}
@@ -4,14 +4,15 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
+use alloc::vec;
 use alloc::vec::Vec;
 
-use crate::{Point, Shader, GradientStop, SpreadMode, Transform};
+use crate::{Color, Point, Shader, GradientStop, SpreadMode, Transform};
 
 use crate::pipeline;
 use crate::scalar::Scalar;
 use crate::wide::u32x8;
-use super::gradient::{Gradient, DEGENERATE_THRESHOLD};
+use super::gradient::{Gradient, DEGENERATE_THRESHOLD, average_gradient_color};
 use crate::pipeline::RasterPipelineBuilder;
 
 #[cfg(all(not(feature = "std"), feature = "libm"))]
@@ -20,6 +21,8 @@ use crate::scalar::FloatExt;
 #[derive(Copy, Clone, Debug)]
 struct FocalData {
     r1: f32, // r1 after mapping focal point to (0, 0)
+    focal_x: f32, // f
+    is_swapped: bool,
 }
 
 impl FocalData {
@@ -36,25 +39,80 @@ impl FocalData {
     fn is_well_behaved(&self) -> bool {
         !self.is_focal_on_circle() && self.r1 > 1.0
     }
+
+    fn is_natively_focal(&self) -> bool {
+        self.focal_x.is_nearly_zero()
+    }
+
+    // From SkTwoPointConicalGradient::FocalData::set
+    fn set(r0: f32, r1: f32, ts: &mut Transform) -> Option<Self> {
+        let mut r0 = r0;
+        let mut r1 = r1;
+        let mut is_swapped = false;
+        let mut focal_x = r0 / (r0 - r1);
+        if (focal_x - 1.0).is_nearly_zero() {
+            // swap r0, r1
+            *ts = ts.post_translate(-1.0, 0.0).post_scale(-1.0, 1.0);
+            core::mem::swap(&mut r0, &mut r1);
+            focal_x = 0.0; // because r0 is now 0
+            is_swapped = true;
+        }
+
+        // Map {focal point, (1, 0)} to {(0, 0), (1, 0)}
+        let focal_ts = Transform::from_poly_to_poly(
+            Point::from_xy(focal_x, 0.0), Point::from_xy(1.0, 0.0),
+            Point::from_xy(0.0, 0.0), Point::from_xy(1.0, 0.0),
+        )?;
+        *ts = ts.post_concat(focal_ts);
+
+        let focal_data = FocalData {
+            r1: r1 / (1.0 - focal_x).abs(), // focal_ts has a scale of 1/(1-f)
+            focal_x,
+            is_swapped,
+        };
+
+        // The following transformations are just to accelerate the shader computation by saving
+        // some arithmetic operations.
+        if focal_data.is_focal_on_circle() {
+            *ts = ts.post_scale(0.5, 0.5);
+        } else {
+            let r1 = focal_data.r1;
+            *ts = ts.post_scale(r1 / (r1 * r1 - 1.0), 1.0 / ((r1 * r1 - 1.0).abs()).sqrt());
+        }
+
+        Some(focal_data)
+    }
+}
+
+
+#[derive(Copy, Clone, Debug)]
+enum GradientType {
+    // Concentric circles.
+    Radial,
+    // Circles of the same radius.
+    Strip { scaled_r0: f32 },
+    Focal(FocalData),
 }
 
 
 /// A radial gradient shader.
 ///
-/// This is not `SkRadialGradient` like in Skia, but rather `SkTwoPointConicalGradient`
-/// without the start radius.
+/// This is not `SkRadialGradient` like in Skia, but rather `SkTwoPointConicalGradient`.
 #[derive(Clone, Debug)]
 pub struct RadialGradient {
     pub(crate) base: Gradient,
     center1: Point,
     center2: Point,
-    radius: f32,
-    focal_data: Option<FocalData>,
+    radius1: f32,
+    radius2: f32,
+    gradient_type: GradientType,
 }
 
 impl RadialGradient {
     /// Creates a new radial gradient shader.
     ///
+    /// Same as [`new_two_point`](#method.new_two_point) with a zero start radius.
+    ///
     /// Returns `Shader::SolidColor` when:
     /// - `stops.len()` == 1
     ///
@@ -71,10 +129,43 @@ impl RadialGradient {
         stops: Vec<GradientStop>,
         mode: SpreadMode,
         transform: Transform,
+    ) -> Option<Shader<'static>> {
+        if radius < 0.0 || radius.is_nearly_zero() {
+            return None;
+        }
+
+        Self::new_two_point(start, 0.0, end, radius, stops, mode, transform)
+    }
+
+    /// Creates a new two-point conical gradient shader.
+    ///
+    /// The gradient is defined by the start circle (`start`, `start_radius`)
+    /// and the end circle (`end`, `end_radius`).
+    /// This is the same as HTML Canvas `createRadialGradient(x0, y0, r0, x1, y1, r1)`
+    /// and SVG `radialGradient` with the `fr` attribute.
+    ///
+    /// Returns `Shader::SolidColor` when:
+    /// - `stops.len()` == 1
+    /// - both circles are the same and `mode` is not `SpreadMode::Pad`
+    ///
+    /// Returns `None` when:
+    ///
+    /// - `stops` is empty
+    /// - `start_radius` < 0 or `end_radius` < 0
+    /// - `transform` is not invertible
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_two_point(
+        start: Point,
+        start_radius: f32,
+        end: Point,
+        end_radius: f32,
+        stops: Vec<GradientStop>,
+        mode: SpreadMode,
+        transform: Transform,
     ) -> Option<Shader<'static>> {
         // From SkGradientShader::MakeTwoPointConical
 
-        if radius < 0.0 || radius.is_nearly_zero() {
+        if start_radius < 0.0 || end_radius < 0.0 {
             return None;
         }
 
@@ -99,18 +190,48 @@ impl RadialGradient {
             // an actual radial gradient (startRadius == 0),
             // or it is fully degenerate (startRadius == endRadius).
 
-            let inv = radius.invert();
-            let mut ts = Transform::from_translate(-start.x, -start.y);
+            if (start_radius - end_radius).is_nearly_zero_within_tolerance(DEGENERATE_THRESHOLD) {
+                // Degenerate case, where the interpolation region area approaches zero.
+                // The proper behavior depends on the tile mode, which is consistent with
+                // the default degenerate gradient behavior, except when mode = Pad and
+                // the radii > DEGENERATE_THRESHOLD.
+                if mode == SpreadMode::Pad && end_radius > DEGENERATE_THRESHOLD {
+                    // The interpolation region becomes an infinitely thin ring at the radius,
+                    // so the final gradient will be the first color repeated from p=0 to 1,
+                    // and then a hard stop switching to the last color at p=1.
+                    let first_color = stops[0].color;
+                    let last_color = stops[stops.len() - 1].color;
+                    let stops = vec![
+                        GradientStop::new(0.0, first_color),
+                        GradientStop::new(1.0, first_color),
+                        GradientStop::new(1.0, last_color),
+                    ];
+
+                    return Self::new_two_point(start, 0.0, end, end_radius, stops, mode, transform);
+                }
+
+                // Otherwise use the default degenerate case.
+                return match mode {
+                    SpreadMode::Pad => Some(Shader::SolidColor(stops[stops.len() - 1].color)),
+                    SpreadMode::Reflect | SpreadMode::Repeat => {
+                        Some(Shader::SolidColor(average_gradient_color(&stops)))
+                    }
+                    SpreadMode::Decal => Some(Shader::SolidColor(Color::TRANSPARENT)),
+                };
+            }
+
+            // Concentric case: we can pretend we're radial (with a tiny twist).
+            let inv = start_radius.max(end_radius).invert();
+            let mut ts = Transform::from_translate(-end.x, -end.y);
             ts = ts.post_scale(inv, inv);
 
-            // We can treat this gradient as radial, which is faster. If we got here, we know
-            // that endRadius is not equal to 0, so this produces a meaningful gradient
             Some(Shader::RadialGradient(RadialGradient {
                 base: Gradient::new(stops, mode, transform, ts),
                 center1: start,
                 center2: end,
-                radius,
-                focal_data: None,
+                radius1: start_radius,
+                radius2: end_radius,
+                gradient_type: GradientType::Radial,
             }))
         } else {
             // From SkTwoPointConicalGradient::Create
@@ -120,64 +241,93 @@ impl RadialGradient {
             )?;
 
             let d_center = (start - end).length();
-            let r1 = radius / d_center;
-            let focal_data = FocalData { r1 };
-
-            // The following transformations are just to accelerate the shader computation by saving
-            // some arithmetic operations.
-            if focal_data.is_focal_on_circle() {
-                ts = ts.post_scale(0.5, 0.5);
+            let gradient_type = if (end_radius - start_radius).is_nearly_zero() {
+                GradientType::Strip { scaled_r0: start_radius / d_center }
             } else {
-                ts = ts.post_scale(r1 / (r1 * r1 - 1.0), 1.0 / ((r1 * r1 - 1.0).abs()).sqrt());
-            }
+                let focal_data = FocalData::set(
+                    start_radius / d_center, end_radius / d_center, &mut ts,
+                )?;
+                GradientType::Focal(focal_data)
+            };
 
             Some(Shader::RadialGradient(RadialGradient {
                 base: Gradient::new(stops, mode, transform, ts),
                 center1: start,
                 center2: end,
-                radius,
-                focal_data: Some(focal_data),
+                radius1: start_radius,
+                radius2: end_radius,
+                gradient_type,
             }))
         }
     }
 
     pub(crate) fn push_stages(&self, p: &mut RasterPipelineBuilder) -> Option<()> {
-        let p0 = if let Some(focal_data) = self.focal_data {
-            1.0 / focal_data.r1
-        } else {
-            1.0
+        let (p0, p1) = match self.gradient_type {
+            GradientType::Radial => {
+                // Radial computes a t for [0, max(r0, r1)], but we want a t for [r0, r1].
+                let d_radius = self.radius2 - self.radius1;
+                (self.radius1.max(self.radius2) / d_radius, -self.radius1 / d_radius)
+            }
+            GradientType::Strip { scaled_r0 } => (scaled_r0 * scaled_r0, 0.0),
+            GradientType::Focal(focal_data) => (1.0 / focal_data.r1, focal_data.focal_x),
         };
 
         p.ctx.two_point_conical_gradient = pipeline::TwoPointConicalGradientCtx {
             mask: u32x8::default(),
             p0,
+            p1,
         };
 
         self.base.push_stages(p,
             &|p| {
-                if let Some(focal_data) = self.focal_data {
-                    // Unlike Skia, we have only the Focal radial gradient type.
-
-                    if focal_data.is_focal_on_circle() {
-                        p.push(pipeline::Stage::XYTo2PtConicalFocalOnCircle);
-                    } else if focal_data.is_well_behaved() {
-                        p.push(pipeline::Stage::XYTo2PtConicalWellBehaved);
-                    } else {
-                        p.push(pipeline::Stage::XYTo2PtConicalGreater);
+                match self.gradient_type {
+                    GradientType::Radial => {
+                        p.push(pipeline::Stage::XYToRadius);
+
+                        if !self.radius1.is_nearly_zero() {
+                            p.push(pipeline::Stage::Alter2PtConicalRadial);
+                        }
+                    }
+                    GradientType::Strip { .. } => {
+                        p.push(pipeline::Stage::XYTo2PtConicalStrip);
+                        p.push(pipeline::Stage::Mask2PtConicalNan);
                     }
+                    GradientType::Focal(focal_data) => {
+                        if focal_data.is_focal_on_circle() {
+                            p.push(pipeline::Stage::XYTo2PtConicalFocalOnCircle);
+                        } else if focal_data.is_well_behaved() {
+                            p.push(pipeline::Stage::XYTo2PtConicalWellBehaved);
+                        } else if focal_data.is_swapped || 1.0 - focal_data.focal_x < 0.0 {
+                            p.push(pipeline::Stage::XYTo2PtConicalSmaller);
+                        } else {
+                            p.push(pipeline::Stage::XYTo2PtConicalGreater);
+                        }
 
-                    if !focal_data.is_well_behaved() {
-                        p.push(pipeline::Stage::Mask2PtConicalDegenerates);
+                        if !focal_data.is_well_behaved() {
+                            p.push(pipeline::Stage::Mask2PtConicalDegenerates);
+                        }
+
+                        if !focal_data.is_natively_focal() {
+                            p.push(pipeline::Stage::Alter2PtConicalCompensateFocal);
+                        }
+
+                        if focal_data.is_swapped {
+                            p.push(pipeline::Stage::Alter2PtConicalUnswap);
+                        }
                     }
-                } else {
-                    p.push(pipeline::Stage::XYToRadius);
                 }
             },
             &|p| {
-                if let Some(focal_data) = self.focal_data {
-                    if !focal_data.is_well_behaved() {
+                match self.gradient_type {
+                    GradientType::Radial => {}
+                    GradientType::Strip { .. } => {
                         p.push(pipeline::Stage::ApplyVectorMask);
                     }
+                    GradientType::Focal(focal_data) => {
+                        if !focal_data.is_well_behaved() {
+                            p.push(pipeline::Stage::ApplyVectorMask);
+                        }
+                    }
                 }
             },
         )
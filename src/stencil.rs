@@ -0,0 +1,235 @@
+// Copyright 2020 Evgeniy Reizner
+//
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{FillRule, LengthU32, Path, PathBuilder, Rect, Transform};
+
+use crate::blitter::Blitter;
+use crate::clip::ClipSegment;
+use crate::geom::IntSize;
+
+/// An operation applied to stencil values covered by a shape.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StencilOp {
+    /// Sets values to zero.
+    Zero,
+    /// Sets values to the specified one.
+    Replace(u8),
+    /// Increments values, clamping to 255.
+    IncrementClamp,
+    /// Decrements values, clamping to 0.
+    DecrementClamp,
+    /// Increments values, wrapping 255 to 0.
+    IncrementWrap,
+    /// Decrements values, wrapping 0 to 255.
+    DecrementWrap,
+    /// Inverts values bits.
+    Invert,
+}
+
+impl StencilOp {
+    #[inline]
+    fn apply(self, v: u8) -> u8 {
+        match self {
+            StencilOp::Zero => 0,
+            StencilOp::Replace(n) => n,
+            StencilOp::IncrementClamp => v.saturating_add(1),
+            StencilOp::DecrementClamp => v.saturating_sub(1),
+            StencilOp::IncrementWrap => v.wrapping_add(1),
+            StencilOp::DecrementWrap => v.wrapping_sub(1),
+            StencilOp::Invert => !v,
+        }
+    }
+}
+
+/// A stencil test function.
+///
+/// Compares a reference value with a stencil value, like `glStencilFunc`.
+/// For example, `Less` passes when `reference < stencil`.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StencilFunc {
+    Never,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Always,
+}
+
+impl StencilFunc {
+    #[inline]
+    pub(crate) fn test(self, reference: u8, v: u8) -> bool {
+        match self {
+            StencilFunc::Never => false,
+            StencilFunc::Less => reference < v,
+            StencilFunc::LessEqual => reference <= v,
+            StencilFunc::Greater => reference > v,
+            StencilFunc::GreaterEqual => reference >= v,
+            StencilFunc::Equal => reference == v,
+            StencilFunc::NotEqual => reference != v,
+            StencilFunc::Always => true,
+        }
+    }
+}
+
+
+/// A stencil test applied during drawing.
+///
+/// Only pixels for which `func` passes for `reference` and a stencil value are drawn.
+/// The stencil must have the same size as the pixmap we're drawing on,
+/// otherwise nothing will be drawn.
+#[derive(Copy, Clone, Debug)]
+pub struct StencilTest<'a> {
+    /// A stencil to test against.
+    pub stencil: &'a Stencil,
+    /// A test function.
+    pub func: StencilFunc,
+    /// A reference value.
+    pub reference: u8,
+}
+
+impl StencilTest<'_> {
+    /// Appends parts of a segment that pass the test to `segments`.
+    pub(crate) fn split_segment(&self, y: u32, segment: ClipSegment, segments: &mut Vec<ClipSegment>) {
+        let width = self.stencil.width() as usize;
+        let start = y as usize * width + segment.x as usize;
+        let row = &self.stencil.data[start..start + segment.width.get() as usize];
+
+        let mut i = 0;
+        while i < row.len() {
+            if !self.func.test(self.reference, row[i]) {
+                i += 1;
+                continue;
+            }
+
+            let left = i;
+            while i < row.len() && self.func.test(self.reference, row[i]) {
+                i += 1;
+            }
+
+            // Cannot fail, since we have at least one value.
+            let width = LengthU32::new((i - left) as u32).unwrap();
+            segments.push(ClipSegment { x: segment.x + left as u32, width, masked: segment.masked });
+        }
+    }
+}
+
+
+/// An 8-bit stencil buffer.
+///
+/// Shapes are rendered into the stencil without anti-aliasing
+/// and modify covered values using a `StencilOp`.
+/// Drawing can be tested against the stencil via `Paint::stencil_test`,
+/// which doesn't require any preprocessing. Or the stencil can be converted
+/// into a regular clip via `ClipMask::set_stencil`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stencil {
+    data: Vec<u8>,
+    size: IntSize,
+}
+
+impl Stencil {
+    /// Creates a new stencil filled with zeros.
+    ///
+    /// Zero size is an error.
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let size = IntSize::from_wh(width, height)?;
+        let len = (width as usize).checked_mul(height as usize)?;
+        Some(Stencil {
+            data: vec![0; len],
+            size,
+        })
+    }
+
+    /// Returns stencil's width.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.size.width()
+    }
+
+    /// Returns stencil's height.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.size.height()
+    }
+
+    /// Returns stencil values.
+    ///
+    /// Row-major, without padding.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns a value at the specified position.
+    ///
+    /// Returns `None` when position is out of bounds.
+    pub fn value(&self, x: u32, y: u32) -> Option<u8> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+
+        self.data.get((y * self.width() + x) as usize).cloned()
+    }
+
+    /// Fills the whole stencil with the specified value.
+    pub fn fill(&mut self, value: u8) {
+        self.data.iter_mut().for_each(|v| *v = value);
+    }
+
+    /// Applies an operation to all values covered by a path.
+    ///
+    /// Returns `None` when there is nothing to fill or in case of a numeric overflow.
+    pub fn fill_path(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        transform: Transform,
+        op: StencilOp,
+    ) -> Option<()> {
+        let transformed_path;
+        let path = if transform.is_identity() {
+            path
+        } else {
+            transformed_path = path.clone().transform(transform)?;
+            &transformed_path
+        };
+
+        let clip = self.size.to_screen_int_rect(0, 0);
+        let mut builder = StencilBuilder {
+            stencil: self,
+            op,
+        };
+
+        crate::scan::path::fill_path(path, fill_rule, &clip, &mut builder)
+    }
+
+    /// Applies an operation to all values covered by a rectangle.
+    ///
+    /// Returns `None` when there is nothing to fill or in case of a numeric overflow.
+    pub fn fill_rect(&mut self, rect: Rect, transform: Transform, op: StencilOp) -> Option<()> {
+        let path = PathBuilder::from_rect(rect);
+        self.fill_path(&path, FillRule::Winding, transform, op)
+    }
+}
+
+
+struct StencilBuilder<'a> {
+    stencil: &'a mut Stencil,
+    op: StencilOp,
+}
+
+impl Blitter for StencilBuilder<'_> {
+    fn blit_h(&mut self, x: u32, y: u32, width: LengthU32) {
+        let offset = (y * self.stencil.width() + x) as usize;
+        for v in &mut self.stencil.data[offset..offset + width.get() as usize] {
+            *v = self.op.apply(*v);
+        }
+    }
+}
@@ -4,7 +4,7 @@ use tiny_skia::*;
 fn rect() {
     let clip_path = PathBuilder::from_rect(Rect::from_xywh(10.0, 10.0, 80.0, 80.0).unwrap());
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
@@ -21,7 +21,7 @@ fn rect() {
 fn rect_aa() {
     let clip_path = PathBuilder::from_rect(Rect::from_xywh(10.5, 10.0, 80.0, 80.5).unwrap());
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
@@ -42,7 +42,7 @@ fn rect_ts() {
     let clip_path = clip_path.transform(Transform::from_row(1.0, -0.3, 0.0, 1.0, 0.0, 15.0)).unwrap();
 
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
@@ -60,7 +60,7 @@ fn circle_bottom_right_aa() {
 
     let clip_path = PathBuilder::from_circle(100.0, 100.0, 50.0).unwrap();
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
@@ -78,7 +78,7 @@ fn stroke() {
 
     let clip_path = PathBuilder::from_rect(Rect::from_xywh(10.0, 10.0, 80.0, 80.0).unwrap());
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false, Transform::identity());
 
     let mut paint = Paint::default();
     paint.set_color_rgba8(50, 127, 150, 200);
@@ -119,7 +119,7 @@ fn skip_dest() {
 
     let clip_path = PathBuilder::from_rect(Rect::from_xywh(40.0, 40.0, 40.0, 40.0).unwrap());
     let mut clip_mask = ClipMask::new();
-    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true);
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true, Transform::identity());
 
     pixmap.draw_pixmap(0, 0, pixmap2.as_ref(), &PixmapPaint::default(),
                                 Transform::identity(), Some(&clip_mask));
@@ -127,3 +127,445 @@ fn skip_dest() {
     let expected = Pixmap::load_png("tests/images/clip/skip-dest.png").unwrap();
     assert_eq!(pixmap, expected);
 }
+
+fn combined_clip(op: ClipOp) -> Pixmap {
+    let mut clip_mask = ClipMask::new();
+    let path = PathBuilder::from_circle(40.0, 50.0, 30.0).unwrap();
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, true, Transform::identity());
+
+    let path = PathBuilder::from_circle(60.0, 50.0, 30.0).unwrap();
+    match op {
+        ClipOp::Intersect => clip_mask.intersect_path(&path, FillRule::Winding, true, Transform::identity()).unwrap(),
+        ClipOp::Union => clip_mask.union_path(&path, FillRule::Winding, true, Transform::identity()).unwrap(),
+        ClipOp::Difference => clip_mask.difference_path(&path, FillRule::Winding, true, Transform::identity()).unwrap(),
+        ClipOp::Xor => clip_mask.xor_path(&path, FillRule::Winding, true, Transform::identity()).unwrap(),
+    }
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+    pixmap.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask));
+    pixmap
+}
+
+#[test]
+fn intersect_path() {
+    let pixmap = combined_clip(ClipOp::Intersect);
+    let expected = Pixmap::load_png("tests/images/clip/intersect-path.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn union_path() {
+    let pixmap = combined_clip(ClipOp::Union);
+    let expected = Pixmap::load_png("tests/images/clip/union-path.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn difference_path() {
+    let pixmap = combined_clip(ClipOp::Difference);
+    let expected = Pixmap::load_png("tests/images/clip/difference-path.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn xor_path() {
+    let pixmap = combined_clip(ClipOp::Xor);
+    let expected = Pixmap::load_png("tests/images/clip/xor-path.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn intersect_path_outside() {
+    let mut clip_mask = ClipMask::new();
+    let path = PathBuilder::from_rect(Rect::from_xywh(10.0, 10.0, 80.0, 80.0).unwrap());
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity());
+
+    let path = PathBuilder::from_rect(Rect::from_xywh(200.0, 10.0, 80.0, 80.0).unwrap());
+    clip_mask.intersect_path(&path, FillRule::Winding, false, Transform::identity()).unwrap();
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+    pixmap.fill_rect(rect, &Paint::default(), Transform::identity(), Some(&clip_mask));
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[test]
+fn combine_masks() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 60.0, 100.0).unwrap());
+    let mut clip_mask1 = ClipMask::new();
+    clip_mask1.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity());
+
+    let path = PathBuilder::from_rect(Rect::from_xywh(40.0, 0.0, 60.0, 100.0).unwrap());
+    let mut clip_mask2 = ClipMask::new();
+    clip_mask2.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity());
+
+    clip_mask1.combine(&clip_mask2, ClipOp::Xor).unwrap();
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+    pixmap.fill_rect(rect, &Paint::default(), Transform::identity(), Some(&clip_mask1));
+    assert_eq!(pixmap.pixel(20, 50).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(80, 50).unwrap().alpha(), 255);
+}
+
+#[test]
+fn combine_invalid() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 60.0, 100.0).unwrap());
+    let mut clip_mask1 = ClipMask::new();
+    clip_mask1.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity());
+
+    let mut clip_mask2 = ClipMask::new();
+    clip_mask2.set_path(50, 50, &path, FillRule::Winding, false, Transform::identity());
+
+    // Different sizes.
+    assert!(clip_mask1.combine(&clip_mask2, ClipOp::Union).is_none());
+    // Empty masks.
+    assert!(clip_mask1.combine(&ClipMask::new(), ClipOp::Union).is_none());
+    assert!(ClipMask::new().intersect_path(&path, FillRule::Winding, false, Transform::identity()).is_none());
+}
+
+#[test]
+fn set_path_with_transform() {
+    let path = PathBuilder::from_circle(0.0, 0.0, 30.0).unwrap();
+    let ts = Transform::from_row(1.5, 0.2, -0.3, 1.0, 50.0, 50.0);
+
+    let mut clip_mask1 = ClipMask::new();
+    clip_mask1.set_path(100, 100, &path, FillRule::Winding, true, ts).unwrap();
+
+    let mut clip_mask2 = ClipMask::new();
+    let transformed_path = path.clone().transform(ts).unwrap();
+    clip_mask2.set_path(100, 100, &transformed_path, FillRule::Winding, true, Transform::identity()).unwrap();
+
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.fill_rect(rect, &Paint::default(), Transform::identity(), Some(&clip_mask1));
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    pixmap2.fill_rect(rect, &Paint::default(), Transform::identity(), Some(&clip_mask2));
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn small_clip_on_large_pixmap() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap());
+    let mut clip_mask = ClipMask::new();
+    let ts = Transform::from_translate(1500.0, 1200.0);
+    clip_mask.set_path(2000, 2000, &path, FillRule::Winding, false, ts).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 255);
+
+    let mut pixmap = Pixmap::new(2000, 2000).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 2000.0, 2000.0).unwrap();
+    pixmap.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask));
+
+    assert_eq!(pixmap.pixel(1500, 1200).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(1509, 1209).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(1499, 1200).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(1510, 1209).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(1500, 1210).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(0, 0).unwrap().alpha(), 0);
+}
+
+#[test]
+fn set_path_outside() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap());
+    let mut clip_mask = ClipMask::new();
+    let ts = Transform::from_translate(200.0, 0.0);
+    assert!(clip_mask.set_path(100, 100, &path, FillRule::Winding, false, ts).is_none());
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+    pixmap.fill_rect(rect, &Paint::default(), Transform::identity(), Some(&clip_mask));
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[test]
+fn reuse_cleared() {
+    let path = PathBuilder::from_circle(50.0, 50.0, 40.0).unwrap();
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, true, Transform::identity()).unwrap();
+    let expected = fill_with_mask(&clip_mask);
+
+    clip_mask.clear();
+    assert!(clip_mask.is_empty());
+
+    // An empty mask must not be used for drawing.
+    let pixmap = fill_with_mask(&clip_mask);
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, true, Transform::identity()).unwrap();
+    assert_eq!(fill_with_mask(&clip_mask), expected);
+}
+
+#[test]
+fn crop() {
+    let path = PathBuilder::from_circle(50.0, 50.0, 40.0).unwrap();
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, true, Transform::identity()).unwrap();
+
+    // Cropping a 200x200 area around the mask is the same as moving the path.
+    let cropped = clip_mask.crop(IntRect::from_xywh(-50, -50, 200, 200).unwrap()).unwrap();
+    let mut expected = ClipMask::new();
+    expected.set_path(200, 200, &path, FillRule::Winding, true,
+                      Transform::from_translate(50.0, 50.0)).unwrap();
+    assert_eq!(fill_square(200, &cropped), fill_square(200, &expected));
+
+    // Outside the mask.
+    let cropped = clip_mask.crop(IntRect::from_xywh(200, 0, 10, 10).unwrap()).unwrap();
+    assert!(!cropped.is_empty());
+    assert!(fill_square(10, &cropped).pixels().iter().all(|p| p.alpha() == 0));
+
+    // Rectangular masks stay rectangular.
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_rect(100, 100, Rect::from_xywh(10.0, 10.0, 50.0, 50.0).unwrap(),
+                       false, Transform::identity()).unwrap();
+    let cropped = clip_mask.crop(IntRect::from_xywh(20, 30, 50, 50).unwrap()).unwrap();
+    let mut expected = ClipMask::new();
+    expected.set_rect(50, 50, Rect::from_xywh(0.0, 0.0, 40.0, 30.0).unwrap(),
+                      false, Transform::identity()).unwrap();
+    assert_eq!(fill_square(50, &cropped), fill_square(50, &expected));
+
+    assert!(ClipMask::new().crop(IntRect::from_xywh(0, 0, 10, 10).unwrap()).is_none());
+}
+
+fn fill_square(size: u32, clip_mask: &ClipMask) -> Pixmap {
+    let mut pixmap = Pixmap::new(size, size).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, size as f32, size as f32).unwrap();
+    pixmap.fill_rect(rect, &Paint::default(), Transform::identity(), Some(clip_mask));
+    pixmap
+}
+
+fn fill_with_mask(clip_mask: &ClipMask) -> Pixmap {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap());
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), Some(clip_mask));
+    pixmap
+}
+
+fn set_rect_is_the_same_as_set_path(rect: Rect, anti_alias: bool, ts: Transform) {
+    let mut clip_mask1 = ClipMask::new();
+    clip_mask1.set_rect(100, 100, rect, anti_alias, ts).unwrap();
+
+    let mut clip_mask2 = ClipMask::new();
+    let path = PathBuilder::from_rect(rect);
+    clip_mask2.set_path(100, 100, &path, FillRule::Winding, anti_alias, ts).unwrap();
+
+    assert_eq!(fill_with_mask(&clip_mask1), fill_with_mask(&clip_mask2));
+}
+
+#[test]
+fn set_rect() {
+    let rect = Rect::from_xywh(10.0, 20.0, 50.0, 60.0).unwrap();
+    set_rect_is_the_same_as_set_path(rect, false, Transform::identity());
+    set_rect_is_the_same_as_set_path(rect, true, Transform::identity());
+}
+
+#[test]
+fn set_rect_fractional() {
+    let rect = Rect::from_xywh(10.3, 20.6, 50.2, 60.7).unwrap();
+    set_rect_is_the_same_as_set_path(rect, false, Transform::identity());
+    set_rect_is_the_same_as_set_path(rect, true, Transform::identity());
+
+    let rect = Rect::from_xywh(10.5, 20.5, 50.0, 60.0).unwrap();
+    set_rect_is_the_same_as_set_path(rect, false, Transform::identity());
+}
+
+#[test]
+fn set_rect_with_transform() {
+    let rect = Rect::from_xywh(10.0, 20.0, 30.0, 30.0).unwrap();
+    let ts = Transform::from_row(2.0, 0.0, 0.0, 1.5, 5.0, -3.0);
+    set_rect_is_the_same_as_set_path(rect, true, ts);
+
+    let ts = Transform::from_row(1.0, 0.3, -0.2, 1.0, 5.0, 3.0);
+    set_rect_is_the_same_as_set_path(rect, true, ts);
+}
+
+#[test]
+fn set_rect_outside() {
+    let rect = Rect::from_xywh(110.0, 20.0, 50.0, 60.0).unwrap();
+    let mut clip_mask = ClipMask::new();
+    assert!(clip_mask.set_rect(100, 100, rect, false, Transform::identity()).is_none());
+    assert!(!clip_mask.is_empty());
+    assert!(fill_with_mask(&clip_mask).pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[test]
+fn intersect_rects() {
+    let mut clip_mask1 = ClipMask::new();
+    let rect = Rect::from_xywh(10.0, 10.0, 50.0, 50.0).unwrap();
+    clip_mask1.set_rect(100, 100, rect, false, Transform::identity()).unwrap();
+
+    let mut clip_mask2 = ClipMask::new();
+    let rect = Rect::from_xywh(40.0, 40.0, 50.0, 50.0).unwrap();
+    clip_mask2.set_rect(100, 100, rect, false, Transform::identity()).unwrap();
+
+    clip_mask1.combine(&clip_mask2, ClipOp::Intersect).unwrap();
+
+    let mut expected = ClipMask::new();
+    let rect = Rect::from_xywh(40.0, 40.0, 20.0, 20.0).unwrap();
+    expected.set_rect(100, 100, rect, false, Transform::identity()).unwrap();
+
+    assert_eq!(fill_with_mask(&clip_mask1), fill_with_mask(&expected));
+}
+
+#[test]
+fn rect_and_path() {
+    let mut clip_mask = ClipMask::new();
+    let rect = Rect::from_xywh(10.0, 10.0, 50.0, 50.0).unwrap();
+    clip_mask.set_rect(100, 100, rect, false, Transform::identity()).unwrap();
+    let path = PathBuilder::from_circle(50.0, 50.0, 20.0).unwrap();
+    clip_mask.union_path(&path, FillRule::Winding, true, Transform::identity()).unwrap();
+
+    let mut expected = ClipMask::new();
+    expected.set_path(100, 100, &PathBuilder::from_rect(rect), FillRule::Winding, false,
+                      Transform::identity()).unwrap();
+    expected.union_path(&path, FillRule::Winding, true, Transform::identity()).unwrap();
+
+    assert_eq!(fill_with_mask(&clip_mask), fill_with_mask(&expected));
+}
+
+#[test]
+fn set_rect_opaque() {
+    let mut clip_mask = ClipMask::new();
+    let rect = Rect::from_xywh(10.0, 20.0, 30.0, 40.0).unwrap();
+    clip_mask.set_rect(100, 100, rect, true, Transform::identity()).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 255);
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+    pixmap.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask));
+
+    assert_eq!(pixmap.pixel(10, 20).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(39, 59).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(9, 20).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(40, 59).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(39, 60).unwrap().alpha(), 0);
+}
+
+fn fill_with_pixmap_mask(mask: &Pixmap, mask_type: MaskType) -> Pixmap {
+    let clip_mask = ClipMask::from_pixmap(mask.as_ref(), mask_type);
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(0, 0, 0, 255);
+
+    let mut pixmap = Pixmap::new(mask.width(), mask.height()).unwrap();
+    let rect = Rect::from_xywh(0.0, 0.0, mask.width() as f32, mask.height() as f32).unwrap();
+    pixmap.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask));
+    pixmap
+}
+
+#[test]
+fn from_pixmap_alpha() {
+    let mut mask = Pixmap::new(4, 1).unwrap();
+    mask.fill(Color::from_rgba8(255, 0, 0, 255));
+    mask.pixels_mut()[1] = ColorU8::from_rgba(0, 0, 0, 128).premultiply();
+    mask.pixels_mut()[2] = ColorU8::from_rgba(0, 0, 0, 0).premultiply();
+
+    let pixmap = fill_with_pixmap_mask(&mask, MaskType::Alpha);
+    let alphas: Vec<u8> = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+    assert_eq!(alphas, &[255, 128, 0, 255]);
+}
+
+#[test]
+fn from_pixmap_luminance() {
+    let mut mask = Pixmap::new(5, 1).unwrap();
+    mask.pixels_mut()[0] = ColorU8::from_rgba(255, 255, 255, 255).premultiply();
+    mask.pixels_mut()[1] = ColorU8::from_rgba(255, 255, 255, 128).premultiply();
+    mask.pixels_mut()[2] = ColorU8::from_rgba(255, 0, 0, 255).premultiply();
+    mask.pixels_mut()[3] = ColorU8::from_rgba(0, 0, 0, 255).premultiply();
+    mask.pixels_mut()[4] = ColorU8::from_rgba(0, 255, 0, 255).premultiply();
+
+    let pixmap = fill_with_pixmap_mask(&mask, MaskType::Luminance);
+    let alphas: Vec<u8> = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+    assert_eq!(alphas, &[255, 128, 54, 0, 182]);
+}
+
+#[test]
+fn from_pixmap_bounded() {
+    let mut mask = Pixmap::new(100, 100).unwrap();
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(255, 255, 255, 255);
+    let path = PathBuilder::from_circle(70.0, 60.0, 10.0).unwrap();
+    mask.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let pixmap = fill_with_pixmap_mask(&mask, MaskType::Luminance);
+    let alphas: Vec<u8> = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+    let expected: Vec<u8> = mask.pixels().iter().map(|p| p.alpha()).collect();
+    assert_eq!(alphas, expected);
+}
+
+#[test]
+fn from_pixmap_empty() {
+    let mask = Pixmap::new(10, 10).unwrap();
+    let clip_mask = ClipMask::from_pixmap(mask.as_ref(), MaskType::Alpha);
+    assert!(!clip_mask.is_empty());
+
+    let pixmap = fill_with_pixmap_mask(&mask, MaskType::Alpha);
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+}
+
+fn draw_aa_shapes(clip_mask: Option<&ClipMask>) -> Pixmap {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 255);
+    paint.anti_alias = true;
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+
+    let mut pb = PathBuilder::new();
+    pb.move_to(5.0, 95.0);
+    pb.line_to(50.3, 3.7);
+    pb.line_to(96.1, 80.2);
+    pb.close();
+    let path = pb.finish().unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), clip_mask);
+
+    let mut pb = PathBuilder::new();
+    pb.move_to(2.0, 10.0);
+    pb.line_to(98.0, 60.5);
+    pb.move_to(30.5, 2.0);
+    pb.line_to(30.5, 98.0);
+    let path = pb.finish().unwrap();
+    let stroke = Stroke::default();
+    paint.set_color_rgba8(200, 50, 50, 200);
+    pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), clip_mask);
+
+    pixmap
+}
+
+#[test]
+fn opaque_runs_are_the_same_as_no_clip() {
+    // A non-anti-aliased rect path produces only fully opaque runs.
+    let mut clip_mask = ClipMask::new();
+    let path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap());
+    clip_mask.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity()).unwrap();
+
+    assert_eq!(draw_aa_shapes(Some(&clip_mask)), draw_aa_shapes(None));
+}
+
+#[test]
+fn partial_runs() {
+    let mut pb = PathBuilder::new();
+    pb.push_circle(50.0, 50.0, 30.0);
+    pb.push_rect(10.5, 10.5, 20.0, 80.0);
+    let path = pb.finish().unwrap();
+
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &path, FillRule::EvenOdd, true, Transform::identity()).unwrap();
+
+    let pixmap = draw_aa_shapes(Some(&clip_mask));
+    let expected = Pixmap::load_png("tests/images/clip/partial-runs.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
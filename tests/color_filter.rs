@@ -0,0 +1,245 @@
+use tiny_skia::*;
+
+fn fill_gradient(paint: &mut Paint) -> Pixmap {
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_circle(100.0, 100.0, 90.0).unwrap();
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, paint, FillRule::Winding, Transform::identity(), None);
+    pixmap
+}
+
+#[test]
+fn matrix() {
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    // Swap red and blue channels and reduce opacity.
+    paint.color_filter = Some(ColorFilter::Matrix([
+        0.0, 0.0, 1.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0, 0.0,
+        1.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.5, 0.0,
+    ]));
+
+    let pixmap = fill_gradient(&mut paint);
+    let expected = Pixmap::load_png("tests/images/color-filter/matrix.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn saturate() {
+    let mut paint = Paint::default();
+    paint.color_filter = Some(ColorFilter::saturate(0.0));
+
+    let pixmap = fill_gradient(&mut paint);
+    for p in pixmap.pixels() {
+        assert!((p.red() as i32 - p.green() as i32).abs() <= 1);
+        assert!((p.green() as i32 - p.blue() as i32).abs() <= 1);
+    }
+}
+
+#[test]
+fn hue_rotate_by_zero() {
+    let mut paint = Paint::default();
+    paint.force_hq_pipeline = true;
+    let pixmap1 = fill_gradient(&mut paint);
+
+    paint.color_filter = Some(ColorFilter::hue_rotate(0.0));
+    let pixmap2 = fill_gradient(&mut paint);
+
+    for (p1, p2) in pixmap1.pixels().iter().zip(pixmap2.pixels()) {
+        assert!((p1.red() as i32 - p2.red() as i32).abs() <= 1);
+        assert!((p1.green() as i32 - p2.green() as i32).abs() <= 1);
+        assert!((p1.blue() as i32 - p2.blue() as i32).abs() <= 1);
+        assert_eq!(p1.alpha(), p2.alpha());
+    }
+}
+
+#[test]
+fn luminance_to_alpha() {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(255, 255, 255, 255);
+    paint.color_filter = Some(ColorFilter::LuminanceToAlpha);
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+    // White is fully opaque, but has no color.
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(0, 0, 0, 255).unwrap());
+
+    paint.set_color_rgba8(0, 255, 0, 255);
+    pixmap.fill(Color::TRANSPARENT);
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(0, 0, 0, 182).unwrap());
+}
+
+#[test]
+fn component_transfer() {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(100, 150, 200, 255);
+    paint.color_filter = Some(ColorFilter::ComponentTransfer {
+        red: TransferFunction::Linear { slope: 0.5, intercept: 0.25 },
+        green: TransferFunction::Table(vec![1.0, 0.0]),
+        blue: TransferFunction::Discrete(vec![0.0, 0.5]),
+        alpha: TransferFunction::Gamma { amplitude: 1.0, exponent: 1.0, offset: -0.5 },
+    });
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    let c = pixmap.pixel(5, 5).unwrap().demultiply();
+    assert_eq!(c.alpha(), 128);
+    assert!((c.red() as i32 - 114).abs() <= 1);
+    assert!((c.green() as i32 - 105).abs() <= 1);
+    assert!((c.blue() as i32 - 128).abs() <= 1);
+}
+
+#[test]
+fn component_transfer_identity() {
+    let mut paint = Paint::default();
+    paint.force_hq_pipeline = true;
+    let pixmap1 = fill_gradient(&mut paint);
+
+    paint.color_filter = Some(ColorFilter::ComponentTransfer {
+        red: TransferFunction::Identity,
+        green: TransferFunction::Table(Vec::new()),
+        blue: TransferFunction::Discrete(Vec::new()),
+        alpha: TransferFunction::Identity,
+    });
+    let pixmap2 = fill_gradient(&mut paint);
+
+    for (p1, p2) in pixmap1.pixels().iter().zip(pixmap2.pixels()) {
+        assert!((p1.red() as i32 - p2.red() as i32).abs() <= 1);
+        assert!((p1.green() as i32 - p2.green() as i32).abs() <= 1);
+        assert!((p1.blue() as i32 - p2.blue() as i32).abs() <= 1);
+        assert!((p1.alpha() as i32 - p2.alpha() as i32).abs() <= 1);
+    }
+}
+
+#[test]
+fn draw_pixmap() {
+    let mut paint = Paint::default();
+    let src = fill_gradient(&mut paint);
+
+    let mut paint = PixmapPaint::default();
+    paint.color_filter = Some(ColorFilter::saturate(0.0));
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.draw_pixmap(0, 0, src.as_ref(), &paint, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/color-filter/draw-pixmap.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+fn cube_text(size: u32, f: impl Fn(f32, f32, f32) -> [f32; 3]) -> String {
+    let mut text = String::new();
+    text.push_str("# Created by a test\n");
+    text.push_str("TITLE \"Test LUT\"\n");
+    text.push_str(&format!("LUT_3D_SIZE {}\n\n", size));
+    let last = (size - 1) as f32;
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                let c = f(r as f32 / last, g as f32 / last, b as f32 / last);
+                text.push_str(&format!("{:.6} {:.6} {:.6}\n", c[0], c[1], c[2]));
+            }
+        }
+    }
+
+    text
+}
+
+#[test]
+fn parse_cube() {
+    let lut = ColorLut3D::from_cube(&cube_text(4, |r, g, b| [r, g, b])).unwrap();
+    assert_eq!(lut.size(), 4);
+
+    let text = "DOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2\nLUT_3D_SIZE 2\n\
+        0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+    assert!(ColorLut3D::from_cube(text).is_some());
+}
+
+#[test]
+fn parse_invalid_cube() {
+    // Not enough entries.
+    assert!(ColorLut3D::from_cube("LUT_3D_SIZE 2\n0 0 0\n1 1 1\n").is_none());
+    // No size.
+    assert!(ColorLut3D::from_cube("0 0 0\n1 1 1\n").is_none());
+    // 1D LUTs are not supported.
+    assert!(ColorLut3D::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_none());
+    // Invalid number.
+    assert!(ColorLut3D::from_cube(&cube_text(2, |r, g, b| [r, g, b]).replace("1.000000", "x")).is_none());
+    // Invalid domain.
+    let text = format!("DOMAIN_MIN 1 1 1\nDOMAIN_MAX 0 0 0\n{}", cube_text(2, |r, g, b| [r, g, b]));
+    assert!(ColorLut3D::from_cube(&text).is_none());
+
+    assert!(ColorLut3D::new(1, vec![[0.0; 3]]).is_none());
+    assert!(ColorLut3D::new(2, vec![[0.0; 3]; 7]).is_none());
+}
+
+#[test]
+fn lut_identity() {
+    let lut = std::sync::Arc::new(ColorLut3D::from_cube(&cube_text(5, |r, g, b| [r, g, b])).unwrap());
+
+    let mut paint = Paint::default();
+    paint.force_hq_pipeline = true;
+    let pixmap1 = fill_gradient(&mut paint);
+
+    for interpolation in &[LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+        paint.color_filter = Some(ColorFilter::Lut3D { lut: lut.clone(), interpolation: *interpolation });
+        let pixmap2 = fill_gradient(&mut paint);
+
+        for (p1, p2) in pixmap1.pixels().iter().zip(pixmap2.pixels()) {
+            assert!((p1.red() as i32 - p2.red() as i32).abs() <= 1);
+            assert!((p1.green() as i32 - p2.green() as i32).abs() <= 1);
+            assert!((p1.blue() as i32 - p2.blue() as i32).abs() <= 1);
+            assert_eq!(p1.alpha(), p2.alpha());
+        }
+    }
+}
+
+#[test]
+fn lut_invert() {
+    // A linear LUT must be reproduced exactly by both interpolation methods.
+    let lut = std::sync::Arc::new(ColorLut3D::from_cube(&cube_text(2, |r, g, b| [1.0 - r, 1.0 - g, 1.0 - b])).unwrap());
+
+    for interpolation in &[LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+        let mut pixmap = Pixmap::new(10, 10).unwrap();
+        pixmap.fill(Color::from_rgba8(50, 127, 200, 255));
+        pixmap.apply_color_filter(&ColorFilter::Lut3D { lut: lut.clone(), interpolation: *interpolation });
+
+        let c = pixmap.pixel(5, 5).unwrap();
+        assert!((c.red() as i32 - 205).abs() <= 1);
+        assert!((c.green() as i32 - 128).abs() <= 1);
+        assert!((c.blue() as i32 - 55).abs() <= 1);
+        assert_eq!(c.alpha(), 255);
+    }
+}
+
+#[test]
+fn lut_tetrahedral() {
+    // A warm look with crushed blues.
+    let lut = ColorLut3D::from_cube(&cube_text(9, |r, g, b| {
+        [r.sqrt(), g * 0.9 + r * 0.1, b * b]
+    })).unwrap();
+
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    let mut pixmap = fill_gradient(&mut paint);
+    pixmap.apply_color_filter(&ColorFilter::Lut3D {
+        lut: std::sync::Arc::new(lut),
+        interpolation: LutInterpolation::Tetrahedral,
+    });
+
+    let expected = Pixmap::load_png("tests/images/color-filter/lut-tetrahedral.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
@@ -613,3 +613,88 @@ fn fill_rect() {
     let expected = Pixmap::load_png("tests/images/canvas/fill-rect.png").unwrap();
     assert_eq!(pixmap, expected);
 }
+
+#[test]
+fn paint_opacity() {
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    paint.opacity = 0.5;
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(90.0, 90.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_circle(50.0, 50.0, 40.0).unwrap();
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/fill/paint-opacity.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn paint_opacity_is_the_same_as_shader_opacity() {
+    let tile = {
+        let mut pixmap = Pixmap::new(20, 20).unwrap();
+        pixmap.fill(Color::from_rgba8(50, 127, 150, 200));
+        pixmap
+    };
+
+    let mut paint = Paint::default();
+    paint.force_hq_pipeline = true;
+    paint.shader = Pattern::new(
+        tile.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Nearest,
+        1.0,
+        Transform::identity(),
+    );
+
+    let rect = Rect::from_xywh(0.0, 0.0, 40.0, 40.0).unwrap();
+
+    let mut pixmap1 = Pixmap::new(40, 40).unwrap();
+    paint.opacity = 0.3;
+    pixmap1.fill_rect(rect, &paint, Transform::identity(), None);
+
+    let mut pixmap2 = Pixmap::new(40, 40).unwrap();
+    paint.opacity = 1.0;
+    paint.shader.apply_opacity(0.3);
+    pixmap2.fill_rect(rect, &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn paint_opacity_memset() {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 255);
+    paint.blend_mode = BlendMode::Source;
+    paint.opacity = 0.5;
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill(Color::WHITE);
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(25, 64, 75, 128).unwrap());
+}
+
+#[test]
+fn paint_opacity_source_over() {
+    // An opaque shader with opacity must not be treated as opaque.
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(0, 0, 0, 255);
+    paint.opacity = 0.0;
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill(Color::WHITE);
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(255, 255, 255, 255).unwrap());
+}
@@ -416,3 +416,412 @@ fn global_opacity() {
     let expected = Pixmap::load_png("tests/images/gradients/global-opacity.png").unwrap();
     assert_eq!(pixmap, expected);
 }
+
+#[test]
+fn two_point_conical_focal() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new_two_point(
+        Point::from_xy(80.0, 90.0),
+        20.0,
+        Point::from_xy(110.0, 110.0),
+        80.0,
+        vec![
+            GradientStop::new(0.25, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(0.75, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/two-point-conical-focal.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn two_point_conical_decreasing_radius() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new_two_point(
+        Point::from_xy(60.0, 100.0),
+        50.0,
+        Point::from_xy(140.0, 100.0),
+        10.0,
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Reflect,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/two-point-conical-decreasing-radius.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn two_point_conical_strip() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new_two_point(
+        Point::from_xy(60.0, 100.0),
+        30.0,
+        Point::from_xy(140.0, 100.0),
+        30.0,
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/two-point-conical-strip.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn concentric_radial_with_start_radius() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new_two_point(
+        Point::from_xy(100.0, 100.0),
+        30.0,
+        Point::from_xy(100.0, 100.0),
+        90.0,
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/concentric-radial-with-start-radius.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+// The same circle in a Pad mode is a hard stop between the first and the last colors.
+#[test]
+fn degenerate_two_point_conical() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new_two_point(
+        Point::from_xy(100.0, 100.0),
+        60.0,
+        Point::from_xy(100.0, 100.0),
+        60.0,
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap.pixel(100, 100).unwrap(), PremultipliedColorU8::from_rgba(40, 100, 118, 200).unwrap());
+    assert_eq!(pixmap.pixel(20, 20).unwrap(), PremultipliedColorU8::from_rgba(155, 99, 53, 180).unwrap());
+}
+
+#[test]
+fn interpolation_premultiplied_srgb() {
+    let mut paint = Paint::default();
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(220, 20, 60, 255)),
+            GradientStop::new(1.0, Color::from_rgba8(0, 0, 255, 0)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.set_gradient_interpolation(GradientInterpolation {
+        premultiplied: true,
+        ..GradientInterpolation::default()
+    });
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/interpolation-premultiplied-srgb.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn interpolation_linear_srgb() {
+    let mut paint = Paint::default();
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(255, 0, 0, 255)),
+            GradientStop::new(1.0, Color::from_rgba8(0, 255, 0, 255)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.set_gradient_interpolation(GradientInterpolation {
+        color_space: InterpolationColorSpace::LinearSrgb,
+        ..GradientInterpolation::default()
+    });
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/interpolation-linear-srgb.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn interpolation_oklab() {
+    let mut paint = Paint::default();
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(0, 0, 255, 255)),
+            GradientStop::new(0.5, Color::from_rgba8(255, 255, 255, 255)),
+            GradientStop::new(1.0, Color::from_rgba8(255, 200, 0, 200)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.set_gradient_interpolation(GradientInterpolation {
+        color_space: InterpolationColorSpace::Oklab,
+        premultiplied: true,
+        ..GradientInterpolation::default()
+    });
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/interpolation-oklab.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn interpolation_oklch_longer_hue() {
+    let mut paint = Paint::default();
+    paint.shader = RadialGradient::new(
+        Point::from_xy(100.0, 100.0),
+        Point::from_xy(100.0, 100.0),
+        90.0,
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(255, 0, 0, 255)),
+            GradientStop::new(1.0, Color::from_rgba8(255, 128, 0, 255)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.set_gradient_interpolation(GradientInterpolation {
+        color_space: InterpolationColorSpace::Oklch,
+        hue_interpolation: HueInterpolation::Longer,
+        premultiplied: false,
+    });
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/interpolation-oklch-longer-hue.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+fn heatmap_stops() -> Vec<GradientStop> {
+    vec![
+        GradientStop::new(0.0, Color::from_rgba8(0, 0, 0, 255)),
+        GradientStop::new(0.1, Color::from_rgba8(30, 0, 100, 255)),
+        GradientStop::new(0.2, Color::from_rgba8(80, 0, 160, 255)),
+        GradientStop::new(0.3, Color::from_rgba8(150, 0, 150, 255)),
+        GradientStop::new(0.4, Color::from_rgba8(210, 20, 90, 255)),
+        GradientStop::new(0.5, Color::from_rgba8(240, 60, 30, 230)),
+        GradientStop::new(0.6, Color::from_rgba8(250, 120, 0, 210)),
+        GradientStop::new(0.7, Color::from_rgba8(250, 180, 0, 190)),
+        GradientStop::new(0.7, Color::from_rgba8(50, 180, 250, 190)),
+        GradientStop::new(0.85, Color::from_rgba8(250, 240, 100, 220)),
+        GradientStop::new(1.0, Color::from_rgba8(255, 255, 255, 255)),
+    ]
+}
+
+#[test]
+fn many_stops_linear_lq() {
+    let mut paint = Paint::default();
+    paint.shader = LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        heatmap_stops(),
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/many-stops-linear-lq.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn many_stops_radial_hq() {
+    let mut paint = Paint::default();
+    paint.force_hq_pipeline = true;
+    paint.shader = RadialGradient::new(
+        Point::from_xy(100.0, 100.0),
+        Point::from_xy(100.0, 100.0),
+        60.0,
+        heatmap_stops(),
+        SpreadMode::Reflect,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/gradients/many-stops-radial-hq.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn lookup_table_matches_exact_evaluation() {
+    // Eight stops are evaluated directly, while nine will use a lookup table.
+    // A redundant stop in the middle of the last segment shouldn't change the result.
+    let colors = [
+        Color::from_rgba8(0, 0, 0, 255),
+        Color::from_rgba8(30, 0, 100, 255),
+        Color::from_rgba8(80, 0, 160, 255),
+        Color::from_rgba8(150, 0, 150, 255),
+        Color::from_rgba8(210, 20, 90, 255),
+        Color::from_rgba8(240, 60, 30, 230),
+        Color::from_rgba8(250, 120, 0, 210),
+        Color::from_rgba8(250, 180, 0, 190),
+    ];
+
+    let stops: Vec<_> = colors.iter().enumerate()
+        .map(|(i, c)| GradientStop::new(i as f32 / 7.0, *c))
+        .collect();
+
+    let render = |stops: Vec<GradientStop>, hq: bool| {
+        let mut paint = Paint::default();
+        paint.force_hq_pipeline = hq;
+        paint.shader = LinearGradient::new(
+            Point::from_xy(0.0, 0.0),
+            Point::from_xy(200.0, 0.0),
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        ).unwrap();
+
+        let mut pixmap = Pixmap::new(200, 1).unwrap();
+        pixmap.fill(Color::WHITE);
+        pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 200.0, 1.0).unwrap(), &paint, Transform::identity(), None);
+        pixmap
+    };
+
+    for hq in [false, true].iter().copied() {
+        let exact = render(stops.clone(), hq);
+
+        let mut stops = stops.clone();
+        stops.insert(7, GradientStop::new(
+            13.0 / 14.0,
+            Color::from_rgba8(250, 150, 0, 200),
+        ));
+        let lut = render(stops, hq);
+
+        for (a, b) in exact.pixels().iter().zip(lut.pixels()) {
+            assert!((a.red() as i32 - b.red() as i32).abs() <= 1);
+            assert!((a.green() as i32 - b.green() as i32).abs() <= 1);
+            assert!((a.blue() as i32 - b.blue() as i32).abs() <= 1);
+            assert!((a.alpha() as i32 - b.alpha() as i32).abs() <= 1);
+        }
+    }
+}
+
+#[test]
+fn lookup_table_follows_shader_changes() {
+    let render = |shader: Shader| {
+        let mut paint = Paint::default();
+        paint.shader = shader;
+
+        let mut pixmap = Pixmap::new(200, 1).unwrap();
+        pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 200.0, 1.0).unwrap(), &paint, Transform::identity(), None);
+        pixmap
+    };
+
+    let shader = LinearGradient::new(
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(200.0, 0.0),
+        heatmap_stops(),
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap();
+
+    let srgb = render(shader.clone());
+
+    let mut linear_shader = shader.clone();
+    linear_shader.set_gradient_interpolation(GradientInterpolation {
+        color_space: InterpolationColorSpace::LinearSrgb,
+        ..GradientInterpolation::default()
+    });
+    assert_ne!(render(linear_shader), srgb);
+
+    let mut transparent_shader = shader;
+    transparent_shader.apply_opacity(0.0);
+    assert!(render(transparent_shader).pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[test]
+fn two_stops_linear_decal() {
+    let mut paint = Paint::default();
+    paint.shader = LinearGradient::new(
+        Point::from_xy(50.0, 50.0),
+        Point::from_xy(150.0, 150.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 200)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 180)),
+        ],
+        SpreadMode::Decal,
+        Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    // Outside of the gradient.
+    assert_eq!(pixmap.pixel(20, 20).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(180, 180).unwrap().alpha(), 0);
+
+    let expected = Pixmap::load_png("tests/images/gradients/two-stops-linear-decal.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
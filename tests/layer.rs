@@ -0,0 +1,118 @@
+use tiny_skia::*;
+
+fn draw_circles(pixmap: &mut PixmapMut, ts: Transform) {
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+
+    paint.set_color_rgba8(50, 127, 150, 255);
+    let path = PathBuilder::from_circle(40.0, 50.0, 30.0).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, ts, None);
+
+    paint.set_color_rgba8(220, 140, 75, 255);
+    let path = PathBuilder::from_circle(60.0, 50.0, 30.0).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, ts, None);
+}
+
+#[test]
+fn group_opacity() {
+    let mut paint = PixmapPaint::default();
+    paint.opacity = 0.5;
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.draw_layer(None, &paint, None, |layer, ts, _| draw_circles(layer, ts)).unwrap();
+
+    let expected = Pixmap::load_png("tests/images/layer/group-opacity.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn bounded() {
+    let mut paint = PixmapPaint::default();
+    paint.opacity = 0.5;
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.draw_layer(None, &paint, None, |layer, ts, _| draw_circles(layer, ts)).unwrap();
+
+    // Bounds are larger than the content and partially outside the pixmap.
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    let bounds = Rect::from_xywh(9.5, -20.0, 90.7, 110.0).unwrap();
+    pixmap2.draw_layer(Some(bounds), &paint, None, |layer, ts, layer_bounds| {
+        assert_eq!(layer.width(), 91);
+        assert_eq!(layer.height(), 90);
+        assert_eq!(layer_bounds, IntRect::from_xywh(9, 0, 91, 90).unwrap());
+        draw_circles(layer, ts)
+    }).unwrap();
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn cropped() {
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let bounds = Rect::from_xywh(0.0, 0.0, 50.0, 100.0).unwrap();
+    pixmap.draw_layer(Some(bounds), &PixmapPaint::default(), None, |layer, ts, _| {
+        draw_circles(layer, ts)
+    }).unwrap();
+
+    assert_ne!(pixmap.pixel(45, 50).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(55, 50).unwrap().alpha(), 0);
+}
+
+#[test]
+fn blend_mode_and_mask() {
+    let clip_path = PathBuilder::from_rect(Rect::from_xywh(0.0, 0.0, 100.0, 50.0).unwrap());
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, false, Transform::identity());
+
+    let mut paint = PixmapPaint::default();
+    paint.blend_mode = BlendMode::Multiply;
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.fill(Color::from_rgba8(200, 200, 50, 255));
+    pixmap.draw_layer(None, &paint, Some(&clip_mask), |layer, ts, _| draw_circles(layer, ts)).unwrap();
+
+    let expected = Pixmap::load_png("tests/images/layer/blend-mode-and-mask.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn nested_clip() {
+    let clip_path = PathBuilder::from_circle(50.0, 50.0, 25.0).unwrap();
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &clip_path, FillRule::Winding, true, Transform::identity());
+
+    let mut paint = PixmapPaint::default();
+    paint.opacity = 0.5;
+
+    let draw_clipped = |layer: &mut PixmapMut, ts: Transform, clip_mask: &ClipMask| {
+        let mut paint = Paint::default();
+        paint.anti_alias = true;
+        paint.set_color_rgba8(50, 127, 150, 255);
+        let path = PathBuilder::from_circle(40.0, 50.0, 30.0).unwrap();
+        layer.fill_path(&path, &paint, FillRule::Winding, ts, Some(clip_mask));
+    };
+
+    // A full-size layer can use the mask as is.
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.draw_layer(None, &paint, None, |layer, ts, _| {
+        draw_clipped(layer, ts, &clip_mask)
+    }).unwrap();
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    let bounds = Rect::from_xywh(15.5, 10.0, 70.0, 80.0).unwrap();
+    pixmap2.draw_layer(Some(bounds), &paint, None, |layer, ts, layer_bounds| {
+        let clip_mask = clip_mask.crop(layer_bounds).unwrap();
+        draw_clipped(layer, ts, &clip_mask)
+    }).unwrap();
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn outside() {
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let bounds = Rect::from_xywh(100.0, 0.0, 50.0, 50.0).unwrap();
+    let mut called = false;
+    assert!(pixmap.draw_layer(Some(bounds), &PixmapPaint::default(), None, |_, _, _| called = true).is_none());
+    assert!(!called);
+}
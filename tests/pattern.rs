@@ -241,3 +241,264 @@ fn filter_bicubic() {
     let expected = Pixmap::load_png("tests/images/pattern/filter-bicubic.png").unwrap();
     assert_eq!(pixmap, expected);
 }
+
+fn crate_checkerboard() -> Pixmap {
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    for (i, p) in pixmap.pixels_mut().iter_mut().enumerate() {
+        let x = i % 200;
+        let y = i / 200;
+        if (x + y) % 2 == 0 {
+            *p = PremultipliedColorU8::from_rgba(0, 0, 0, 255).unwrap();
+        } else {
+            *p = PremultipliedColorU8::from_rgba(255, 255, 255, 255).unwrap();
+        }
+    }
+
+    pixmap
+}
+
+#[test]
+fn filter_trilinear() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Trilinear,
+        1.0,
+        Transform::from_row(0.3, 0.1, -0.1, 0.3, 5.0, 1.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/pattern/filter-trilinear.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn filter_trilinear_downscaled_checkerboard() {
+    let checkerboard = crate_checkerboard();
+
+    let mut paint = PixmapPaint::default();
+    paint.quality = FilterQuality::Trilinear;
+
+    let mut pixmap = Pixmap::new(40, 40).unwrap();
+    pixmap.draw_pixmap(0, 0, checkerboard.as_ref(), &paint, Transform::from_scale(0.15, 0.15), None);
+
+    // Checkerboard must be averaged to gray instead of producing a moire.
+    for y in 2..28 {
+        for x in 2..28 {
+            let c = pixmap.pixel(x, y).unwrap();
+            assert!((c.red() as i32 - 128).abs() <= 2, "{:?}", c);
+            assert_eq!(c.alpha(), 255);
+        }
+    }
+}
+
+#[test]
+fn filter_trilinear_mipmap_cache() {
+    let triangle = crate_triangle();
+    let new_shader = || Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Trilinear,
+        1.0,
+        Transform::identity(),
+    );
+
+    let render = |shader: Shader, scale: f32| {
+        let mut paint = Paint::default();
+        paint.shader = shader;
+
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        pixmap.fill_rect(
+            Rect::from_xywh(0.0, 0.0, 200.0, 200.0).unwrap(),
+            &paint,
+            Transform::from_scale(scale, scale),
+            None,
+        );
+        pixmap
+    };
+
+    // Cached levels must be reused and extended when sampling a lower level.
+    let shader = new_shader();
+    for scale in [0.6, 0.2, 0.6, 0.05].iter().copied() {
+        assert_eq!(render(shader.clone(), scale), render(new_shader(), scale));
+    }
+}
+
+#[test]
+fn filter_trilinear_without_downscale() {
+    let triangle = crate_triangle();
+
+    let mut pixmaps = Vec::new();
+    for quality in [FilterQuality::Bilinear, FilterQuality::Trilinear].iter() {
+        let mut paint = Paint::default();
+        paint.shader = Pattern::new(
+            triangle.as_ref(),
+            SpreadMode::Reflect,
+            *quality,
+            0.8,
+            Transform::from_row(1.5, 0.2, -0.3, 1.6, 5.0, 1.0),
+        );
+
+        let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 90.0, 90.0).unwrap());
+
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+        pixmaps.push(pixmap);
+    }
+
+    assert_eq!(pixmaps[0], pixmaps[1]);
+}
+
+#[test]
+fn repeat_x_decal_y_nearest() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new_with_tile_modes(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        SpreadMode::Decal,
+        FilterQuality::Nearest,
+        1.0,
+        Transform::from_translate(0.0, 90.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    // Only a horizontal strip is visible.
+    assert_eq!(pixmap.pixel(100, 50).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(100, 150).unwrap().alpha(), 0);
+
+    let expected = Pixmap::load_png("tests/images/pattern/repeat-x-decal-y-nearest.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn decal_bicubic() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Decal,
+        FilterQuality::Bicubic,
+        1.0,
+        Transform::from_row(4.5, 0.0, -1.2, 2.4, 50.0, 50.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/pattern/decal-bicubic.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn reflect_x_pad_y_bilinear() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new_with_tile_modes(
+        triangle.as_ref(),
+        SpreadMode::Reflect,
+        SpreadMode::Pad,
+        FilterQuality::Bilinear,
+        1.0,
+        Transform::from_row(1.5, 0.0, -0.4, -0.8, 5.0, 1.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/pattern/reflect-x-pad-y-bilinear.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn filter_cubic_catmull_rom() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Cubic(CubicResampler::CATMULL_ROM),
+        1.0,
+        Transform::from_row(1.5, 0.0, -0.4, -0.8, 5.0, 1.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/pattern/filter-cubic-catmull-rom.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn filter_cubic_mitchell() {
+    let triangle = crate_triangle();
+
+    let mut pixmaps = Vec::new();
+    for quality in [FilterQuality::Bicubic, FilterQuality::Cubic(CubicResampler::MITCHELL)].iter() {
+        let mut paint = Paint::default();
+        paint.shader = Pattern::new(
+            triangle.as_ref(),
+            SpreadMode::Repeat,
+            *quality,
+            1.0,
+            Transform::from_row(1.5, 0.0, -0.4, -0.8, 5.0, 1.0),
+        );
+
+        let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+        let mut pixmap = Pixmap::new(200, 200).unwrap();
+        pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+        pixmaps.push(pixmap);
+    }
+
+    // Polynomials are evaluated differently, so we can have rounding errors.
+    for (c1, c2) in pixmaps[0].pixels().iter().zip(pixmaps[1].pixels()) {
+        assert!((c1.red() as i32 - c2.red() as i32).abs() <= 1);
+        assert!((c1.green() as i32 - c2.green() as i32).abs() <= 1);
+        assert!((c1.blue() as i32 - c2.blue() as i32).abs() <= 1);
+        assert!((c1.alpha() as i32 - c2.alpha() as i32).abs() <= 1);
+    }
+}
+
+#[test]
+fn filter_lanczos3() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Lanczos3,
+        1.0,
+        Transform::from_row(1.5, 0.0, -0.4, -0.8, 5.0, 1.0),
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/pattern/filter-lanczos3.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
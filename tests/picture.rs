@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use tiny_skia::*;
+
+fn crate_picture() -> Picture<'static> {
+    let mut recorder = PictureRecorder::new(Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap());
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+
+    let path = PathBuilder::from_circle(10.0, 10.0, 7.0).unwrap();
+    recorder.fill_path(&path, &paint, FillRule::Winding, Transform::identity());
+
+    paint.set_color_rgba8(220, 140, 75, 180);
+    let mut stroke = Stroke::default();
+    stroke.width = 2.0;
+    recorder.stroke_path(&path, &paint, &stroke, Transform::from_translate(3.0, 3.0));
+
+    recorder.finish()
+}
+
+#[test]
+fn draw_picture() {
+    let picture = crate_picture();
+    let ts = Transform::from_row(3.0, 0.5, -0.5, 3.0, 20.0, 10.0);
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.draw_picture(&picture, ts, None);
+
+    // Must be the same as drawing directly.
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(50, 127, 150, 200);
+        paint.anti_alias = true;
+
+        let path = PathBuilder::from_circle(10.0, 10.0, 7.0).unwrap();
+        pixmap2.fill_path(&path, &paint, FillRule::Winding, ts, None);
+
+        paint.set_color_rgba8(220, 140, 75, 180);
+        let mut stroke = Stroke::default();
+        stroke.width = 2.0;
+        pixmap2.stroke_path(&path, &paint, &stroke, Transform::from_translate(3.0, 3.0).post_concat(ts), None);
+    }
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn picture_shader() {
+    let mut paint = Paint::default();
+    paint.shader = PictureShader::new(
+        Arc::new(crate_picture()),
+        Some(Rect::from_xywh(0.0, 0.0, 25.0, 25.0).unwrap()),
+        SpreadMode::Repeat,
+        FilterQuality::Bilinear,
+        1.0,
+        Transform::from_row(1.2, 0.3, -0.3, 1.2, 5.0, 5.0),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::from_scale(2.0, 2.0), None);
+
+    let expected = Pixmap::load_png("tests/images/picture/picture-shader.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn picture_shader_is_sharp() {
+    let picture = crate_picture();
+
+    let mut paint = Paint::default();
+    paint.shader = PictureShader::new(
+        Arc::new(picture.clone()),
+        None,
+        SpreadMode::Pad,
+        FilterQuality::Bilinear,
+        1.0,
+        Transform::identity(),
+    ).unwrap();
+
+    // The tile will be rendered at the device scale,
+    // so the result must be the same as drawing the picture directly.
+    let rect = Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap();
+    let mut pixmap1 = Pixmap::new(80, 80).unwrap();
+    pixmap1.fill_rect(rect, &paint, Transform::from_scale(4.0, 4.0), None);
+
+    let mut pixmap2 = Pixmap::new(80, 80).unwrap();
+    pixmap2.draw_picture(&picture, Transform::from_scale(4.0, 4.0), None);
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn picture_shader_tile_cache() {
+    let picture = Arc::new(crate_picture());
+    let new_shader = || PictureShader::new(
+        picture.clone(),
+        None,
+        SpreadMode::Repeat,
+        FilterQuality::Bilinear,
+        1.0,
+        Transform::identity(),
+    ).unwrap();
+
+    let render = |shader: Shader, scale: f32| {
+        let mut paint = Paint::default();
+        paint.shader = shader;
+
+        let mut pixmap = Pixmap::new(80, 80).unwrap();
+        pixmap.fill_rect(
+            Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap(),
+            &paint,
+            Transform::from_scale(scale, scale),
+            None,
+        );
+        pixmap
+    };
+
+    // A cached tile must be reused only for the same scale.
+    let shader = new_shader();
+    for scale in [2.0, 2.0, 3.5, 2.0].iter().copied() {
+        assert_eq!(render(shader.clone(), scale), render(new_shader(), scale));
+    }
+}
+
+#[test]
+fn picture_shader_with_opacity() {
+    let mut paint = Paint::default();
+    paint.shader = PictureShader::new(
+        Arc::new(crate_picture()),
+        None,
+        SpreadMode::Repeat,
+        FilterQuality::Nearest,
+        1.0,
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.apply_opacity(0.5);
+
+    let mut pixmap = Pixmap::new(20, 20).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap(), &paint, Transform::identity(), None);
+
+    let mut expected = Pixmap::new(20, 20).unwrap();
+    expected.draw_picture(&crate_picture(), Transform::identity(), None);
+
+    let c1 = pixmap.pixel(10, 10).unwrap();
+    let c2 = expected.pixel(10, 10).unwrap();
+    assert!((c1.alpha() as i32 - (c2.alpha() as i32 + 1) / 2).abs() <= 1);
+}
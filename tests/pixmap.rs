@@ -72,6 +72,139 @@ fn fill() {
     assert_eq!(pixmap.pixel(1, 1).unwrap(), c.premultiply().to_color_u8());
 }
 
+// A 3x2 pixmap with unique opaque pixels.
+fn small_pixmap() -> Pixmap {
+    let mut pixmap = Pixmap::new(3, 2).unwrap();
+    for (i, p) in pixmap.pixels_mut().iter_mut().enumerate() {
+        *p = PremultipliedColorU8::from_rgba(i as u8 * 10, 0, 0, 255).unwrap();
+    }
+
+    pixmap
+}
+
+fn reds(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap.pixels().iter().map(|p| p.red()).collect()
+}
+
+#[test]
+fn flip() {
+    let pixmap = small_pixmap();
+    assert_eq!(reds(&pixmap.flip_horizontal()), &[20, 10, 0, 50, 40, 30]);
+    assert_eq!(reds(&pixmap.flip_vertical()), &[30, 40, 50, 0, 10, 20]);
+}
+
+#[test]
+fn rotate() {
+    let pixmap = small_pixmap();
+
+    let rotated = pixmap.rotate_90().unwrap();
+    assert_eq!((rotated.width(), rotated.height()), (2, 3));
+    assert_eq!(reds(&rotated), &[30, 0, 40, 10, 50, 20]);
+
+    assert_eq!(reds(&pixmap.rotate_180()), &[50, 40, 30, 20, 10, 0]);
+
+    let rotated = pixmap.rotate_270().unwrap();
+    assert_eq!((rotated.width(), rotated.height()), (2, 3));
+    assert_eq!(reds(&rotated), &[20, 50, 10, 40, 0, 30]);
+
+    assert_eq!(rotated.rotate_90().unwrap(), pixmap);
+}
+
+#[test]
+fn crop() {
+    let pixmap = small_pixmap();
+    let fill = Color::from_rgba8(0, 0, 255, 255);
+
+    let cropped = pixmap.crop(IntRect::from_xywh(1, 0, 2, 2).unwrap(), fill).unwrap();
+    assert_eq!(reds(&cropped), &[10, 20, 40, 50]);
+
+    let cropped = pixmap.crop(IntRect::from_xywh(2, -1, 2, 2).unwrap(), fill).unwrap();
+    assert_eq!(reds(&cropped), &[0, 0, 20, 0]);
+    assert_eq!(cropped.pixel(0, 0).unwrap().blue(), 255);
+    assert_eq!(cropped.pixel(0, 1).unwrap().blue(), 0);
+
+    let cropped = pixmap.crop(IntRect::from_xywh(10, 10, 2, 2).unwrap(), fill).unwrap();
+    assert!(cropped.pixels().iter().all(|p| p.blue() == 255));
+}
+
+#[test]
+fn extend() {
+    let pixmap = small_pixmap();
+    let fill = Color::from_rgba8(0, 0, 255, 255);
+
+    let extended = pixmap.extend(1, 2, 3, 4, fill).unwrap();
+    assert_eq!((extended.width(), extended.height()), (7, 8));
+    assert_eq!(extended.pixel(0, 0).unwrap().blue(), 255);
+    assert_eq!(extended.pixel(1, 2).unwrap().red(), 0);
+    assert_eq!(extended.pixel(3, 3).unwrap().red(), 50);
+    assert_eq!(extended.pixel(3, 3).unwrap().blue(), 0);
+    assert_eq!(extended.pixel(4, 3).unwrap().blue(), 255);
+
+    assert!(pixmap.extend(u32::MAX, 0, 0, 0, fill).is_none());
+}
+
+#[test]
+fn resize_same_size() {
+    let pixmap = small_pixmap();
+    assert_eq!(pixmap.resize(3, 2, FilterQuality::Bicubic).unwrap(), pixmap);
+}
+
+#[test]
+fn resize_downscale_average() {
+    let mut pixmap = Pixmap::new(4, 2).unwrap();
+    pixmap.pixels_mut()[0] = PremultipliedColorU8::from_rgba(100, 0, 0, 255).unwrap();
+    pixmap.pixels_mut()[3] = PremultipliedColorU8::from_rgba(0, 200, 0, 200).unwrap();
+
+    let resized = pixmap.resize(2, 1, FilterQuality::Bilinear).unwrap();
+    assert_eq!(resized.pixel(0, 0).unwrap(), PremultipliedColorU8::from_rgba(25, 0, 0, 64).unwrap());
+    assert_eq!(resized.pixel(1, 0).unwrap(), PremultipliedColorU8::from_rgba(0, 50, 0, 50).unwrap());
+}
+
+#[test]
+fn resize_downscale_fractional() {
+    // Every pixel of a 3px row contributes to both pixels of a 2px one.
+    let pixmap = small_pixmap();
+    let resized = pixmap.resize(2, 1, FilterQuality::Bicubic).unwrap();
+    // (0 + 30) * 1/3 + (10 + 40) * 1/6 = 18.33
+    // (20 + 50) * 1/3 + (10 + 40) * 1/6 = 31.67
+    assert_eq!(reds(&resized), &[18, 32]);
+}
+
+#[test]
+fn resize_nearest() {
+    let pixmap = small_pixmap();
+    let resized = pixmap.resize(6, 2, FilterQuality::Nearest).unwrap();
+    assert_eq!(reds(&resized), &[0, 0, 10, 10, 20, 20, 30, 30, 40, 40, 50, 50]);
+}
+
+#[test]
+fn resize_upscale() {
+    let mut src = Pixmap::new(20, 20).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+
+    src.fill_path(
+        &PathBuilder::from_circle(10.0, 10.0, 8.0).unwrap(),
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+
+    let pixmap = src.resize(100, 60, FilterQuality::Bicubic).unwrap();
+
+    let expected = Pixmap::load_png("tests/images/pixmap/resize-upscale.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn resize_invalid() {
+    let pixmap = small_pixmap();
+    assert!(pixmap.resize(0, 10, FilterQuality::Bicubic).is_none());
+}
+
 #[test]
 fn draw_pixmap() {
     // Tests that painting algorithm will switch `Bicubic`/`Bilinear` to `Nearest`.
@@ -169,3 +302,222 @@ fn draw_pixmap_opacity() {
     let expected = Pixmap::load_png("tests/images/canvas/draw-pixmap-opacity.png").unwrap();
     assert_eq!(pixmap, expected);
 }
+
+// A 20x10 sprite sheet with a red and a blue 10x10 sprites.
+fn crate_sprite_sheet() -> Pixmap {
+    let mut pixmap = Pixmap::new(20, 10).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(255, 0, 0, 255);
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    paint.set_color_rgba8(0, 0, 255, 255);
+    pixmap.fill_rect(Rect::from_xywh(10.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    pixmap
+}
+
+#[test]
+fn draw_pixmap_rect() {
+    let sheet = crate_sprite_sheet();
+
+    let mut paint = PixmapPaint::default();
+    paint.quality = FilterQuality::Bicubic;
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.draw_pixmap_rect(
+        sheet.as_ref(),
+        Rect::from_xywh(10.0, 0.0, 10.0, 10.0).unwrap(),
+        Rect::from_xywh(20.0, 20.0, 160.0, 80.0).unwrap(),
+        &paint,
+        Transform::from_row(1.0, 0.3, 0.0, 1.0, 0.0, 0.0),
+        None,
+    );
+
+    let expected = Pixmap::load_png("tests/images/canvas/draw-pixmap-rect.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn draw_pixmap_rect_strict() {
+    let sheet = crate_sprite_sheet();
+
+    let mut paint = PixmapPaint::default();
+    paint.quality = FilterQuality::Bilinear;
+
+    let src_rect = Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap();
+    let dst_rect = Rect::from_xywh(0.0, 0.0, 80.0, 80.0).unwrap();
+
+    // Bilinear filtering bleeds the blue sprite.
+    let mut pixmap = Pixmap::new(80, 80).unwrap();
+    pixmap.draw_pixmap_rect(sheet.as_ref(), src_rect, dst_rect, &paint, Transform::identity(), None);
+    assert_ne!(pixmap.pixel(79, 40).unwrap().blue(), 0);
+
+    paint.strict = true;
+    let mut pixmap = Pixmap::new(80, 80).unwrap();
+    pixmap.draw_pixmap_rect(sheet.as_ref(), src_rect, dst_rect, &paint, Transform::identity(), None);
+    assert!(pixmap.pixels().iter().all(|c| *c == PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap()));
+}
+
+#[test]
+fn draw_pixmap_rect_outside() {
+    let sheet = crate_sprite_sheet();
+
+    // `src_rect` is partially outside, therefore `dst_rect` will be shrunk to 40..80.
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.draw_pixmap_rect(
+        sheet.as_ref(),
+        Rect::from_xywh(-10.0, 0.0, 20.0, 10.0).unwrap(),
+        Rect::from_xywh(0.0, 0.0, 80.0, 40.0).unwrap(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        None,
+    );
+
+    assert_eq!(pixmap.pixel(20, 20).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(60, 20).unwrap().red(), 255);
+
+    // Completely outside.
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    assert!(pixmap.draw_pixmap_rect(
+        sheet.as_ref(),
+        Rect::from_xywh(30.0, 0.0, 10.0, 10.0).unwrap(),
+        Rect::from_xywh(0.0, 0.0, 80.0, 40.0).unwrap(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        None,
+    ).is_none());
+}
+
+#[test]
+fn from_bytes_with_stride() {
+    let data = vec![0; 100];
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, 20).is_some());
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, -20).is_some());
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, 16).is_some());
+    // Too small.
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, 12).is_none());
+    // Not a multiple of 4.
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, 18).is_none());
+    // Not enough data.
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, 24).is_none());
+    assert!(PixmapRef::from_bytes_with_stride(&data, 4, 5, isize::MIN).is_none());
+
+    // The last row doesn't need padding.
+    let pixmap = PixmapRef::from_bytes_with_stride(&data[..96], 4, 5, 20).unwrap();
+    assert_eq!(pixmap.stride(), 20);
+}
+
+fn fill_circle(pixmap: &mut PixmapMut) {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+
+    pixmap.fill_path(
+        &PathBuilder::from_circle(10.0, 10.0, 8.0).unwrap(),
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+}
+
+#[test]
+fn padded_rows() {
+    let mut expected = Pixmap::new(20, 20).unwrap();
+    fill_circle(&mut expected.as_mut());
+
+    // 4 pixels of padding marked with 7.
+    let stride = 24 * 4;
+    let mut data = vec![0; stride * 20];
+    data.chunks_mut(stride).for_each(|row| row[80..].iter_mut().for_each(|v| *v = 7));
+    fill_circle(&mut PixmapMut::from_bytes_with_stride(&mut data, 20, 20, stride as isize).unwrap());
+
+    let pixmap = PixmapRef::from_bytes_with_stride(&data, 20, 20, stride as isize).unwrap();
+    assert_eq!(pixmap.to_owned(), expected);
+    assert_eq!(pixmap, expected.as_ref());
+    assert!(data.chunks(stride).all(|row| row[80..].iter().all(|v| *v == 7)));
+}
+
+#[test]
+fn bottom_up_rows() {
+    let mut expected = Pixmap::new(20, 20).unwrap();
+    fill_circle(&mut expected.as_mut());
+    // Make it asymmetric.
+    expected.fill_rect(Rect::from_xywh(0.0, 0.0, 5.0, 2.0).unwrap(), &Paint::default(), Transform::identity(), None);
+
+    let mut data = vec![0; 20 * 20 * 4];
+    {
+        let mut pixmap = PixmapMut::from_bytes_with_stride(&mut data, 20, 20, -20 * 4).unwrap();
+        fill_circle(&mut pixmap);
+        pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 5.0, 2.0).unwrap(), &Paint::default(), Transform::identity(), None);
+    }
+
+    let pixmap = PixmapRef::from_bytes_with_stride(&data, 20, 20, -20 * 4).unwrap();
+    assert_eq!(pixmap.to_owned(), expected);
+    assert_eq!(pixmap.pixel(1, 1), expected.pixel(1, 1));
+    // The bottom row goes first in memory.
+    assert_eq!(&data[..], expected.flip_vertical().data());
+}
+
+#[test]
+fn sub_pixmap() {
+    let mut expected = Pixmap::new(20, 20).unwrap();
+    expected.fill(Color::WHITE);
+    fill_circle(&mut expected.as_mut());
+
+    let mut pixmap = Pixmap::new(40, 40).unwrap();
+    pixmap.fill(Color::WHITE);
+    fill_circle(&mut pixmap.as_mut().sub_pixmap(IntRect::from_xywh(10, 5, 20, 20).unwrap()).unwrap());
+
+    let part = pixmap.as_ref().sub_pixmap(IntRect::from_xywh(10, 5, 20, 20).unwrap()).unwrap();
+    assert_eq!(part.stride(), 40 * 4);
+    assert_eq!(part.to_owned(), expected);
+    assert_eq!(pixmap.pixel(9, 5).unwrap(), PremultipliedColorU8::from_rgba(255, 255, 255, 255).unwrap());
+
+    assert!(pixmap.as_ref().sub_pixmap(IntRect::from_xywh(40, 0, 10, 10).unwrap()).is_none());
+}
+
+#[test]
+fn bottom_up_sub_pixmap() {
+    let mut expected = Pixmap::new(20, 20).unwrap();
+    fill_circle(&mut expected.as_mut());
+
+    let mut data = vec![0; 40 * 40 * 4];
+    {
+        let mut pixmap = PixmapMut::from_bytes_with_stride(&mut data, 40, 40, -40 * 4).unwrap();
+        fill_circle(&mut pixmap.sub_pixmap(IntRect::from_xywh(10, 5, 20, 20).unwrap()).unwrap());
+    }
+
+    let pixmap = PixmapRef::from_bytes_with_stride(&data, 40, 40, -40 * 4).unwrap();
+    let part = pixmap.sub_pixmap(IntRect::from_xywh(10, 5, 20, 20).unwrap()).unwrap();
+    assert_eq!(part.to_owned(), expected);
+    assert_eq!(pixmap.to_owned().clone_rect(IntRect::from_xywh(10, 5, 20, 20).unwrap()).unwrap(), expected);
+}
+
+#[test]
+fn draw_strided_pixmap() {
+    let mut src = Pixmap::new(20, 20).unwrap();
+    fill_circle(&mut src.as_mut());
+
+    let mut paint = PixmapPaint::default();
+    paint.quality = FilterQuality::Bicubic;
+    let ts = Transform::from_row(1.5, 0.2, 0.3, 1.2, 3.0, 4.0);
+
+    let mut expected = Pixmap::new(50, 50).unwrap();
+    expected.draw_pixmap(0, 0, src.as_ref(), &paint, ts, None);
+
+    // Store the source bottom-up inside a larger buffer.
+    let mut data = vec![255; 30 * 25 * 4];
+    PixmapMut::from_bytes_with_stride(&mut data, 30, 25, -30 * 4).unwrap()
+        .sub_pixmap(IntRect::from_xywh(5, 3, 20, 20).unwrap()).unwrap()
+        .draw_pixmap(0, 0, src.as_ref(), &PixmapPaint { blend_mode: BlendMode::Source, ..PixmapPaint::default() }, Transform::identity(), None);
+
+    let strided = PixmapRef::from_bytes_with_stride(&data, 30, 25, -30 * 4).unwrap()
+        .sub_pixmap(IntRect::from_xywh(5, 3, 20, 20).unwrap()).unwrap();
+    assert_eq!(strided, src.as_ref());
+
+    let mut pixmap = Pixmap::new(50, 50).unwrap();
+    pixmap.draw_pixmap(0, 0, strided, &paint, ts, None);
+    assert_eq!(pixmap, expected);
+}
@@ -0,0 +1,208 @@
+use tiny_skia::*;
+
+fn rect(x: i32, y: i32, w: u32, h: u32) -> IntRect {
+    IntRect::from_xywh(x, y, w, h).unwrap()
+}
+
+#[test]
+fn union_overlapping() {
+    let mut region = Region::from_rect(rect(0, 0, 20, 20));
+    region.combine_rect(rect(10, 10, 20, 20), ClipOp::Union);
+
+    assert_eq!(region.rects(), &[
+        rect(0, 0, 20, 10),
+        rect(0, 10, 30, 10),
+        rect(10, 20, 20, 10),
+    ]);
+    assert_eq!(region.bounds(), Some(rect(0, 0, 30, 30)));
+}
+
+#[test]
+fn union_adjacent() {
+    let mut region = Region::from_rect(rect(0, 0, 10, 10));
+    region.combine_rect(rect(10, 0, 10, 10), ClipOp::Union);
+    region.combine_rect(rect(0, 10, 20, 10), ClipOp::Union);
+
+    assert!(region.is_rect());
+    assert_eq!(region.rects(), &[rect(0, 0, 20, 20)]);
+}
+
+#[test]
+fn intersect() {
+    let mut region = Region::from_rect(rect(0, 0, 20, 20));
+    region.combine_rect(rect(10, 10, 20, 20), ClipOp::Intersect);
+    assert_eq!(region.rects(), &[rect(10, 10, 10, 10)]);
+
+    region.combine_rect(rect(50, 50, 10, 10), ClipOp::Intersect);
+    assert!(region.is_empty());
+    assert_eq!(region.bounds(), None);
+}
+
+#[test]
+fn difference() {
+    let mut region = Region::from_rect(rect(0, 0, 30, 30));
+    region.combine_rect(rect(10, 10, 10, 10), ClipOp::Difference);
+
+    assert_eq!(region.rects(), &[
+        rect(0, 0, 30, 10),
+        rect(0, 10, 10, 10),
+        rect(20, 10, 10, 10),
+        rect(0, 20, 30, 10),
+    ]);
+
+    assert!(region.contains(5, 15));
+    assert!(!region.contains(15, 15));
+    assert!(!region.contains(30, 15));
+    assert!(region.contains_rect(rect(0, 0, 30, 10)));
+    assert!(!region.contains_rect(rect(0, 0, 30, 11)));
+    assert!(region.intersects_rect(rect(5, 5, 10, 10)));
+    assert!(!region.intersects_rect(rect(12, 12, 5, 5)));
+}
+
+#[test]
+fn xor() {
+    let mut region = Region::from_rect(rect(0, 0, 20, 10));
+    region.combine_rect(rect(10, 0, 20, 10), ClipOp::Xor);
+
+    assert_eq!(region.rects(), &[
+        rect(0, 0, 10, 10),
+        rect(20, 0, 10, 10),
+    ]);
+}
+
+#[test]
+fn combine_is_canonical() {
+    let mut region1 = Region::from_rect(rect(0, 0, 10, 30));
+    region1.combine_rect(rect(10, 0, 10, 30), ClipOp::Union);
+
+    let mut region2 = Region::from_rect(rect(0, 0, 20, 15));
+    region2.combine_rect(rect(0, 15, 20, 15), ClipOp::Union);
+
+    assert_eq!(region1, region2);
+}
+
+#[test]
+fn translate() {
+    let mut region = Region::from_rect(rect(0, 0, 10, 10));
+    region.translate(-5, 20).unwrap();
+    assert_eq!(region.rects(), &[rect(-5, 20, 10, 10)]);
+    assert!(region.translate(i32::MAX, 0).is_none());
+    assert_eq!(region.rects(), &[rect(-5, 20, 10, 10)]);
+}
+
+#[test]
+fn huge_rects() {
+    const LIMIT: i32 = i32::MAX / 2;
+
+    let mut region = Region::from_rect(IntRect::from_ltrb(-2_000_000_000, 0, 0, 10).unwrap());
+    region.combine_rect(IntRect::from_ltrb(0, 0, 2_000_000_000, 10).unwrap(), ClipOp::Union);
+    assert_eq!(region.rects(), &[IntRect::from_ltrb(-LIMIT, 0, LIMIT, 10).unwrap()]);
+    assert_eq!(region.bounds(), IntRect::from_ltrb(-LIMIT, 0, LIMIT, 10));
+
+    let mut region = Region::from_rect(IntRect::from_ltrb(0, -2_000_000_000, 10, 0).unwrap());
+    region.combine_rect(IntRect::from_ltrb(0, 0, 10, 2_000_000_000).unwrap(), ClipOp::Union);
+    assert_eq!(region.rects(), &[IntRect::from_ltrb(0, -LIMIT, 10, LIMIT).unwrap()]);
+    assert_eq!(region.bounds(), IntRect::from_ltrb(0, -LIMIT, 10, LIMIT));
+
+    assert!(!region.contains_rect(IntRect::from_ltrb(0, 0, 10, 2_000_000_000).unwrap()));
+    assert!(region.translate(0, 1).is_none());
+    assert!(Region::from_rect(IntRect::from_ltrb(2_000_000_000, 0, 2_000_000_010, 10).unwrap()).is_empty());
+}
+
+#[test]
+fn combine_many_bands() {
+    let path = PathBuilder::from_circle(2000.0, 2000.0, 1990.0).unwrap();
+    let region = Region::from_path(&path, FillRule::Winding, Transform::identity(),
+                                   rect(0, 0, 4000, 4000)).unwrap();
+    assert!(region.rects().len() > 2000);
+
+    // Each rect is in its own band and spans are not clipped,
+    // so this is the same as clipping each of them.
+    let clip = rect(0, 200, 4000, 3000);
+    let mut clipped = region.clone();
+    clipped.combine_rect(clip, ClipOp::Intersect);
+    let expected: Vec<IntRect> = region.rects().iter().filter_map(|r| {
+        IntRect::from_ltrb(
+            r.left().max(clip.left()),
+            r.top().max(clip.top()),
+            r.right().min(clip.right()),
+            r.bottom().min(clip.bottom()),
+        )
+    }).collect();
+    assert_eq!(clipped.rects(), expected.as_slice());
+
+    assert!(region.contains_rect(rect(1000, 1000, 2000, 2000)));
+    assert!(!region.contains_rect(rect(0, 0, 2000, 2000)));
+}
+
+#[test]
+fn from_rect_path() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(10.0, 20.0, 30.0, 40.0).unwrap());
+    let region = Region::from_path(&path, FillRule::Winding, Transform::identity(),
+                                   rect(0, 0, 100, 100)).unwrap();
+    assert_eq!(region.rects(), &[rect(10, 20, 30, 40)]);
+}
+
+#[test]
+fn from_path() {
+    let mut pb = PathBuilder::new();
+    pb.push_circle(50.0, 50.0, 40.0);
+    pb.push_circle(50.0, 50.0, 20.0);
+    let path = pb.finish().unwrap();
+    let ts = Transform::from_row(1.0, 0.0, 0.3, 1.0, -10.0, 0.0);
+
+    let region = Region::from_path(&path, FillRule::EvenOdd, ts, rect(0, 0, 100, 100)).unwrap();
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.fill_path(&path, &Paint::default(), FillRule::EvenOdd, ts, None);
+
+    for y in 0..100 {
+        for x in 0..100 {
+            let filled = pixmap.pixel(x, y).unwrap().alpha() != 0;
+            assert_eq!(region.contains(x as i32, y as i32), filled);
+        }
+    }
+}
+
+#[test]
+fn from_path_clipped() {
+    let path = PathBuilder::from_rect(Rect::from_xywh(10.0, 20.0, 30.0, 40.0).unwrap());
+    let region = Region::from_path(&path, FillRule::Winding, Transform::identity(),
+                                   rect(20, 0, 100, 30)).unwrap();
+    assert_eq!(region.rects(), &[rect(20, 20, 20, 10)]);
+
+    let region = Region::from_path(&path, FillRule::Winding, Transform::identity(),
+                                   rect(50, 50, 10, 10)).unwrap();
+    assert!(region.is_empty());
+
+    assert!(Region::from_path(&path, FillRule::Winding, Transform::identity(),
+                              rect(-10, 0, 100, 100)).is_none());
+}
+
+#[test]
+fn clip_mask_from_region() {
+    let mut region = Region::from_rect(rect(10, 10, 40, 40));
+    region.combine_rect(rect(30, 30, 40, 40), ClipOp::Union);
+
+    let mut clip_mask1 = ClipMask::new();
+    clip_mask1.set_region(100, 100, &region).unwrap();
+
+    let mut pb = PathBuilder::new();
+    pb.push_rect(10.0, 10.0, 40.0, 40.0);
+    pb.push_rect(30.0, 30.0, 40.0, 40.0);
+    let path = pb.finish().unwrap();
+    let mut clip_mask2 = ClipMask::new();
+    clip_mask2.set_path(100, 100, &path, FillRule::Winding, false, Transform::identity()).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    let rect = Rect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap();
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask1));
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    pixmap2.fill_rect(rect, &paint, Transform::identity(), Some(&clip_mask2));
+
+    assert_eq!(pixmap1, pixmap2);
+}
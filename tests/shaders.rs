@@ -0,0 +1,388 @@
+use tiny_skia::*;
+
+fn crate_triangle() -> Pixmap {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+
+    let mut pb = PathBuilder::new();
+    pb.move_to(0.0, 20.0);
+    pb.line_to(20.0, 20.0);
+    pb.line_to(10.0, 0.0);
+    pb.close();
+    let path = pb.finish().unwrap();
+
+    let mut pixmap = Pixmap::new(20, 20).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+    pixmap
+}
+
+fn crate_circle() -> Pixmap {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(220, 140, 75, 180);
+    paint.anti_alias = true;
+
+    let path = PathBuilder::from_circle(15.0, 15.0, 10.0).unwrap();
+
+    let mut pixmap = Pixmap::new(30, 30).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+    pixmap
+}
+
+fn gradient() -> Shader<'static> {
+    LinearGradient::new(
+        Point::from_xy(10.0, 10.0),
+        Point::from_xy(190.0, 190.0),
+        vec![
+            GradientStop::new(0.0, Color::from_rgba8(50, 127, 150, 255)),
+            GradientStop::new(1.0, Color::from_rgba8(220, 140, 75, 255)),
+        ],
+        SpreadMode::Pad,
+        Transform::identity(),
+    ).unwrap()
+}
+
+#[test]
+fn blend_gradient_with_pattern() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Shader::Blend {
+        src: Box::new(Pattern::new(
+            triangle.as_ref(),
+            SpreadMode::Repeat,
+            FilterQuality::Nearest,
+            1.0,
+            Transform::identity(),
+        )),
+        dst: Box::new(gradient()),
+        mode: BlendMode::Multiply,
+    };
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/blend-gradient-with-pattern.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn blend_two_patterns() {
+    let triangle = crate_triangle();
+    let circle = crate_circle();
+
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    paint.shader = Shader::Blend {
+        src: Box::new(Pattern::new(
+            triangle.as_ref(),
+            SpreadMode::Repeat,
+            FilterQuality::Bicubic,
+            1.0,
+            Transform::from_row(1.5, 0.0, 0.0, 1.5, 0.0, 0.0),
+        )),
+        dst: Box::new(Pattern::new(
+            circle.as_ref(),
+            SpreadMode::Reflect,
+            FilterQuality::Nearest,
+            1.0,
+            Transform::identity(),
+        )),
+        mode: BlendMode::Xor,
+    };
+
+    let path = PathBuilder::from_circle(100.0, 100.0, 90.0).unwrap();
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/blend-two-patterns.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn nested_composition() {
+    let triangle = crate_triangle();
+
+    let mut paint = Paint::default();
+    paint.shader = Shader::Lerp {
+        a: Box::new(gradient()),
+        b: Box::new(Shader::Blend {
+            src: Box::new(Pattern::new(
+                triangle.as_ref(),
+                SpreadMode::Repeat,
+                FilterQuality::Bilinear,
+                1.0,
+                Transform::from_row(2.0, 0.0, 0.0, 2.0, 3.0, 3.0),
+            )),
+            dst: Box::new(RadialGradient::new(
+                Point::from_xy(100.0, 100.0),
+                Point::from_xy(100.0, 100.0),
+                90.0,
+                vec![
+                    GradientStop::new(0.0, Color::from_rgba8(255, 255, 255, 255)),
+                    GradientStop::new(1.0, Color::from_rgba8(0, 0, 0, 0)),
+                ],
+                SpreadMode::Pad,
+                Transform::identity(),
+            ).unwrap()),
+            mode: BlendMode::SourceIn,
+        }),
+        t: 0.75,
+    };
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/nested-composition.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn lerp_solid_colors() {
+    let mut paint = Paint::default();
+    paint.shader = Shader::Lerp {
+        a: Box::new(Shader::SolidColor(Color::from_rgba8(255, 0, 0, 255))),
+        b: Box::new(Shader::SolidColor(Color::from_rgba8(0, 0, 255, 255))),
+        t: 0.25,
+    };
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(191, 0, 64, 255).unwrap());
+}
+
+#[test]
+fn blend_opacity() {
+    let mut paint = Paint::default();
+    paint.shader = Shader::Blend {
+        src: Box::new(Shader::SolidColor(Color::from_rgba8(0, 0, 255, 255))),
+        dst: Box::new(Shader::SolidColor(Color::from_rgba8(255, 0, 0, 255))),
+        mode: BlendMode::SourceOut,
+    };
+    paint.shader.apply_opacity(0.5);
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    // SourceOut of two opaque colors is transparent, so opacity must not be applied
+    // to the children, otherwise we will get a semi-transparent blue.
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::TRANSPARENT);
+}
+
+#[test]
+fn with_local_matrix() {
+    let triangle = crate_triangle();
+    let ts = Transform::from_row(1.2, 0.3, -0.3, 1.2, 5.0, 7.0);
+
+    let mut paint1 = Paint::default();
+    paint1.shader = Shader::WithLocalMatrix {
+        shader: Box::new(Pattern::new(
+            triangle.as_ref(),
+            SpreadMode::Repeat,
+            FilterQuality::Bilinear,
+            1.0,
+            Transform::identity(),
+        )),
+        transform: ts,
+    };
+
+    let mut paint2 = Paint::default();
+    paint2.shader = Pattern::new(
+        triangle.as_ref(),
+        SpreadMode::Repeat,
+        FilterQuality::Bilinear,
+        1.0,
+        ts,
+    );
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 90.0, 90.0).unwrap());
+    let path_ts = Transform::from_translate(5.0, 5.0);
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.fill_path(&path, &paint1, FillRule::Winding, path_ts, None);
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    pixmap2.fill_path(&path, &paint2, FillRule::Winding, path_ts, None);
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn fractal_noise() {
+    let mut paint = Paint::default();
+    paint.shader = PerlinNoise::new_fractal_noise(
+        0.02, 0.04, 4, 2.0, None, Transform::identity(),
+    ).unwrap();
+
+    let path = PathBuilder::from_rect(Rect::from_ltrb(10.0, 10.0, 190.0, 190.0).unwrap());
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/fractal-noise.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn turbulence_stitched_with_transform() {
+    let mut paint = Paint::default();
+    paint.shader = PerlinNoise::new_turbulence(
+        0.05, 0.05, 3, 0.0,
+        Some(Rect::from_xywh(0.0, 0.0, 50.0, 50.0).unwrap()),
+        Transform::from_row(1.5, 0.2, -0.2, 1.5, 0.0, 0.0),
+    ).unwrap();
+
+    let path = PathBuilder::from_circle(100.0, 100.0, 90.0).unwrap();
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/turbulence-stitched-with-transform.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn noise_with_opacity() {
+    let mut paint = Paint::default();
+    paint.shader = PerlinNoise::new_turbulence(
+        0.05, 0.05, 1, 0.0, None, Transform::identity(),
+    ).unwrap();
+
+    let mut pixmap1 = Pixmap::new(20, 20).unwrap();
+    pixmap1.fill_rect(Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap(), &paint, Transform::identity(), None);
+
+    paint.shader.apply_opacity(0.0);
+    let mut pixmap2 = Pixmap::new(20, 20).unwrap();
+    pixmap2.fill_rect(Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert!(pixmap1.pixels().iter().any(|p| p.alpha() != 0));
+    assert!(pixmap2.pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[test]
+fn noise_many_octaves() {
+    let render = |num_octaves, stitch_tile| {
+        let mut paint = Paint::default();
+        paint.shader = PerlinNoise::new_fractal_noise(
+            0.05, 0.05, num_octaves, 2.0, stitch_tile, Transform::identity(),
+        ).unwrap();
+
+        let mut pixmap = Pixmap::new(50, 50).unwrap();
+        pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 50.0, 50.0).unwrap(), &paint, Transform::identity(), None);
+        pixmap
+    };
+
+    let stitch_tile = Rect::from_xywh(0.0, 0.0, 64.0, 64.0);
+    for &stitch_tile in &[None, stitch_tile] {
+        // Further octaves must not affect the result.
+        let expected = render(24, stitch_tile);
+        for &num_octaves in &[32, 40, 255, u32::MAX] {
+            let pixmap = render(num_octaves, stitch_tile);
+            for (p1, p2) in pixmap.pixels().iter().zip(expected.pixels()) {
+                assert!((p1.red() as i32 - p2.red() as i32).abs() <= 1);
+                assert!((p1.alpha() as i32 - p2.alpha() as i32).abs() <= 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn noise_invalid_frequency() {
+    assert!(PerlinNoise::new_fractal_noise(-0.1, 0.1, 1, 0.0, None, Transform::identity()).is_none());
+    assert!(PerlinNoise::new_turbulence(0.1, core::f32::NAN, 1, 0.0, None, Transform::identity()).is_none());
+}
+
+#[test]
+fn custom_closure() {
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    paint.shader = CustomShader::new(|x: f32, y: f32| {
+        let r = x / 200.0;
+        let g = y / 200.0;
+        let a = if ((x / 20.0) as i32 + (y / 20.0) as i32) % 2 == 0 { 1.0 } else { 0.5 };
+        Color::from_rgba(r, g, 0.5, a).unwrap().premultiply()
+    }, Transform::identity()).unwrap();
+
+    let path = PathBuilder::from_circle(100.0, 100.0, 90.0).unwrap();
+
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/shaders/custom-closure.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+struct Stripes;
+
+impl CustomShaderFn for Stripes {
+    fn shade(
+        &self,
+        x: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        _: &[f32; CUSTOM_SHADER_BATCH_SIZE],
+        colors: &mut [PremultipliedColor; CUSTOM_SHADER_BATCH_SIZE],
+    ) {
+        for (x, c) in x.iter().zip(colors.iter_mut()) {
+            if (*x as i32) % 2 == 0 {
+                *c = Color::BLACK.premultiply();
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_trait() {
+    let mut paint = Paint::default();
+    paint.shader = CustomShader::new(Stripes, Transform::from_scale(2.0, 2.0)).unwrap();
+
+    let mut pixmap = Pixmap::new(20, 20).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap(), &paint, Transform::identity(), None);
+
+    // Pixels not touched by `shade` are transparent.
+    for (i, p) in pixmap.pixels().iter().enumerate() {
+        let x = i % 20;
+        let expected = if (x / 2) % 2 == 0 { 255 } else { 0 };
+        assert_eq!(p.alpha(), expected);
+    }
+}
+
+#[test]
+fn custom_with_opacity() {
+    let mut paint = Paint::default();
+    paint.shader = CustomShader::new(
+        |_, _| Color::WHITE.premultiply(),
+        Transform::identity(),
+    ).unwrap();
+    paint.shader.apply_opacity(0.5);
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 128 && p.red() == 128));
+}
+
+#[test]
+fn custom_invalid_transform() {
+    let ts = Transform::from_row(1.0, 1.0, 1.0, 1.0, 0.0, 0.0);
+    assert!(CustomShader::new(|_, _| Color::WHITE.premultiply(), ts).is_none());
+}
+
+#[test]
+fn blend_arithmetic() {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(200, 0, 100, 255);
+    paint.blender = Some(Blender::Arithmetic { k1: 0.0, k2: 0.5, k3: 0.5, k4: 0.0 });
+    // Must be ignored.
+    paint.blend_mode = BlendMode::Destination;
+
+    let mut pixmap = Pixmap::new(10, 10).unwrap();
+    pixmap.fill(Color::from_rgba8(0, 100, 200, 255));
+    pixmap.fill_rect(Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap(), &paint, Transform::identity(), None);
+
+    assert_eq!(pixmap.pixel(5, 5).unwrap(), PremultipliedColorU8::from_rgba(100, 50, 150, 255).unwrap());
+}
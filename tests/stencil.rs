@@ -0,0 +1,211 @@
+use tiny_skia::*;
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+    Rect::from_xywh(x, y, w, h).unwrap()
+}
+
+#[test]
+fn ops() {
+    let mut stencil = Stencil::new(6, 1).unwrap();
+    stencil.fill(1);
+
+    let ts = Transform::identity();
+    stencil.fill_rect(rect(0.0, 0.0, 1.0, 1.0), ts, StencilOp::Zero).unwrap();
+    stencil.fill_rect(rect(1.0, 0.0, 1.0, 1.0), ts, StencilOp::Replace(7)).unwrap();
+    stencil.fill_rect(rect(2.0, 0.0, 1.0, 1.0), ts, StencilOp::IncrementClamp).unwrap();
+    stencil.fill_rect(rect(3.0, 0.0, 1.0, 1.0), ts, StencilOp::DecrementClamp).unwrap();
+    stencil.fill_rect(rect(3.0, 0.0, 1.0, 1.0), ts, StencilOp::DecrementClamp).unwrap();
+    stencil.fill_rect(rect(4.0, 0.0, 1.0, 1.0), ts, StencilOp::DecrementWrap).unwrap();
+    stencil.fill_rect(rect(4.0, 0.0, 1.0, 1.0), ts, StencilOp::DecrementWrap).unwrap();
+    stencil.fill_rect(rect(5.0, 0.0, 1.0, 1.0), ts, StencilOp::Invert).unwrap();
+
+    assert_eq!(stencil.data(), &[0, 7, 2, 0, 255, 254]);
+
+    stencil.fill_rect(rect(4.0, 0.0, 2.0, 1.0), ts, StencilOp::IncrementWrap).unwrap();
+    stencil.fill_rect(rect(4.0, 0.0, 2.0, 1.0), ts, StencilOp::IncrementClamp).unwrap();
+    assert_eq!(stencil.data(), &[0, 7, 2, 0, 1, 255]);
+}
+
+#[test]
+fn fill_path() {
+    let mut pb = PathBuilder::new();
+    pb.push_rect(0.0, 0.0, 10.0, 10.0);
+    pb.push_rect(2.0, 2.0, 6.0, 6.0);
+    let path = pb.finish().unwrap();
+
+    let mut stencil = Stencil::new(20, 20).unwrap();
+    let ts = Transform::from_translate(5.0, 5.0);
+    stencil.fill_path(&path, FillRule::EvenOdd, ts, StencilOp::Replace(3)).unwrap();
+
+    assert_eq!(stencil.value(5, 5), Some(3));
+    assert_eq!(stencil.value(14, 14), Some(3));
+    assert_eq!(stencil.value(10, 10), Some(0));
+    assert_eq!(stencil.value(4, 5), Some(0));
+    assert_eq!(stencil.value(15, 15), Some(0));
+    assert_eq!(stencil.value(20, 0), None);
+}
+
+#[test]
+fn knock_out() {
+    let mut stencil = Stencil::new(100, 100).unwrap();
+    let ts = Transform::identity();
+    stencil.fill_rect(rect(10.0, 10.0, 50.0, 50.0), ts, StencilOp::IncrementClamp).unwrap();
+    stencil.fill_rect(rect(40.0, 40.0, 50.0, 50.0), ts, StencilOp::IncrementClamp).unwrap();
+
+    // Draw only where exactly one shape is present.
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_stencil(&stencil, StencilFunc::Equal, 1);
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let path = PathBuilder::from_circle(50.0, 50.0, 45.0).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), Some(&clip_mask));
+
+    let expected = Pixmap::load_png("tests/images/stencil/knock-out.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn stencil_then_cover() {
+    let mut pb = PathBuilder::new();
+    pb.move_to(10.0, 90.0);
+    pb.line_to(50.0, 5.0);
+    pb.line_to(90.0, 90.0);
+    pb.line_to(5.0, 35.0);
+    pb.line_to(95.0, 35.0);
+    pb.close();
+    let path = pb.finish().unwrap();
+
+    let mut stencil = Stencil::new(100, 100).unwrap();
+    stencil.fill_path(&path, FillRule::EvenOdd, Transform::identity(),
+                      StencilOp::Replace(1)).unwrap();
+
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_stencil(&stencil, StencilFunc::NotEqual, 0);
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.fill_rect(rect(0.0, 0.0, 100.0, 100.0), &paint, Transform::identity(), Some(&clip_mask));
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    pixmap2.fill_path(&path, &paint, FillRule::EvenOdd, Transform::identity(), None);
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn funcs() {
+    let mut stencil = Stencil::new(3, 1).unwrap();
+    stencil.fill_rect(rect(1.0, 0.0, 1.0, 1.0), Transform::identity(), StencilOp::Replace(1)).unwrap();
+    stencil.fill_rect(rect(2.0, 0.0, 1.0, 1.0), Transform::identity(), StencilOp::Replace(2)).unwrap();
+
+    let check = |func, expected: &[u8]| {
+        let mut clip_mask = ClipMask::new();
+        clip_mask.set_stencil(&stencil, func, 1);
+
+        let mut pixmap = Pixmap::new(3, 1).unwrap();
+        pixmap.fill_rect(rect(0.0, 0.0, 3.0, 1.0), &Paint::default(),
+                         Transform::identity(), Some(&clip_mask));
+        let alphas: Vec<u8> = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+        assert_eq!(alphas, expected);
+    };
+
+    check(StencilFunc::Never, &[0, 0, 0]);
+    check(StencilFunc::Less, &[0, 0, 255]);
+    check(StencilFunc::LessEqual, &[0, 255, 255]);
+    check(StencilFunc::Greater, &[255, 0, 0]);
+    check(StencilFunc::GreaterEqual, &[255, 255, 0]);
+    check(StencilFunc::Equal, &[0, 255, 0]);
+    check(StencilFunc::NotEqual, &[255, 0, 255]);
+    check(StencilFunc::Always, &[255, 255, 255]);
+}
+
+#[test]
+fn stencil_test() {
+    let mut stencil = Stencil::new(100, 100).unwrap();
+    let ts = Transform::identity();
+    stencil.fill_rect(rect(10.0, 10.0, 50.0, 50.0), ts, StencilOp::IncrementClamp).unwrap();
+    stencil.fill_rect(rect(40.0, 40.0, 50.0, 50.0), ts, StencilOp::IncrementClamp).unwrap();
+
+    // Must be the same as `knock_out`, but without a clip mask.
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.anti_alias = true;
+    paint.stencil_test = Some(StencilTest { stencil: &stencil, func: StencilFunc::Equal, reference: 1 });
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let path = PathBuilder::from_circle(50.0, 50.0, 45.0).unwrap();
+    pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+
+    let expected = Pixmap::load_png("tests/images/stencil/knock-out.png").unwrap();
+    assert_eq!(pixmap, expected);
+}
+
+#[test]
+fn stencil_test_after_stencil_update() {
+    let mut stencil = Stencil::new(100, 100).unwrap();
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    for (i, path) in [
+        PathBuilder::from_circle(30.0, 30.0, 25.0).unwrap(),
+        PathBuilder::from_circle(70.0, 60.0, 35.0).unwrap(),
+    ].iter().enumerate() {
+        // Stencil, then cover.
+        let reference = i as u8 + 1;
+        stencil.fill_path(path, FillRule::Winding, Transform::identity(),
+                          StencilOp::Replace(reference)).unwrap();
+
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(50, 127, 150, 200);
+        pixmap2.fill_path(path, &paint, FillRule::Winding, Transform::identity(), None);
+
+        paint.stencil_test = Some(StencilTest { stencil: &stencil, func: StencilFunc::Equal, reference });
+        pixmap1.fill_rect(rect(0.0, 0.0, 100.0, 100.0), &paint, Transform::identity(), None);
+    }
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn stencil_test_with_clip_mask() {
+    let mut stencil = Stencil::new(100, 100).unwrap();
+    stencil.fill_rect(rect(0.0, 0.0, 50.0, 100.0), Transform::identity(), StencilOp::Replace(1)).unwrap();
+
+    let circle = PathBuilder::from_circle(50.0, 50.0, 40.0).unwrap();
+    let mut clip_mask = ClipMask::new();
+    clip_mask.set_path(100, 100, &circle, FillRule::Winding, true, Transform::identity()).unwrap();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(50, 127, 150, 200);
+    paint.stencil_test = Some(StencilTest { stencil: &stencil, func: StencilFunc::Equal, reference: 1 });
+
+    let mut pixmap1 = Pixmap::new(100, 100).unwrap();
+    pixmap1.fill_rect(rect(0.0, 0.0, 100.0, 100.0), &paint, Transform::identity(), Some(&clip_mask));
+
+    // Both must be applied.
+    clip_mask.intersect_path(&PathBuilder::from_rect(rect(0.0, 0.0, 50.0, 100.0)),
+                             FillRule::Winding, false, Transform::identity()).unwrap();
+    paint.stencil_test = None;
+
+    let mut pixmap2 = Pixmap::new(100, 100).unwrap();
+    pixmap2.fill_rect(rect(0.0, 0.0, 100.0, 100.0), &paint, Transform::identity(), Some(&clip_mask));
+
+    assert_eq!(pixmap1, pixmap2);
+}
+
+#[test]
+fn stencil_test_size_mismatch() {
+    let stencil = Stencil::new(50, 50).unwrap();
+
+    let mut paint = Paint::default();
+    paint.stencil_test = Some(StencilTest { stencil: &stencil, func: StencilFunc::Always, reference: 0 });
+
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.fill_rect(rect(0.0, 0.0, 100.0, 100.0), &paint, Transform::identity(), None);
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+}